@@ -22,5 +22,19 @@ fn bench_rolling_apply_lazy(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_rolling_apply_eager, bench_rolling_apply_lazy);
+fn bench_rolling_sum(c: &mut Criterion) {
+    let data: Vec<_> = (0..LENGTH).collect();
+    let ctx = Context::new(data);
+    let expr = s(0).rolling(100).sum();
+    c.bench_function("rolling_sum", |b| {
+        b.iter(|| expr.eval(&ctx, Some(Backend::Vec)))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_rolling_apply_eager,
+    bench_rolling_apply_lazy,
+    bench_rolling_sum
+);
 criterion_main!(benches);
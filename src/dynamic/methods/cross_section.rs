@@ -0,0 +1,291 @@
+use crate::prelude::*;
+use tevec::ndarray::parallel::prelude::*;
+use tevec::ndarray::{ArrayD, Axis};
+
+/// Transforms on the cross section of a panel, e.g. the assets of a
+/// time x asset array at each time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossSection {
+    /// average rank of the values, divided by the count of valid values if `pct` is true
+    Rank { pct: bool },
+    /// subtract the mean and divide by the sample standard deviation
+    ZScore,
+    /// subtract the mean
+    Demean,
+    /// divide by the sum of the absolute values, so that the gross exposure is one
+    Scale,
+    /// divide by the sum of the values, so that the values sum to one
+    Normalize,
+    /// clip the outliers to the bounds computed by the method
+    Winsorize(WinsorizeBounds),
+}
+
+impl CrossSection {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            CrossSection::Rank { .. } => "cs_rank",
+            CrossSection::ZScore => "cs_zscore",
+            CrossSection::Demean => "cs_demean",
+            CrossSection::Scale => "cs_scale",
+            CrossSection::Normalize => "cs_normalize",
+            // not prefixed to match `Expr::winsorize`, which is also used on series
+            CrossSection::Winsorize(_) => "winsorize",
+        }
+    }
+
+    #[inline]
+    pub fn check(&self) -> TResult<()> {
+        if let CrossSection::Winsorize(method) = self {
+            method.check()?;
+        }
+        Ok(())
+    }
+}
+
+/// transform a cross section, NaN values are excluded from the statistics and
+/// are kept as NaN. The result is NaN if the statistic used as the divisor is
+/// zero or can not be calculated.
+pub fn cross_section(values: &[f64], op: CrossSection) -> Vec<f64> {
+    let valid = || values.iter().copied().filter(|v| !v.is_nan());
+    let (n, sum) = valid().fold((0, 0.), |(n, sum), v| (n + 1, sum + v));
+    let mean = sum / n as f64;
+    let divide = |d: f64| -> Vec<f64> {
+        if d == 0. || d.is_nan() {
+            vec![f64::NAN; values.len()]
+        } else {
+            values.iter().map(|v| v / d).collect()
+        }
+    };
+    match op {
+        CrossSection::Rank { pct } => {
+            rank_by(values.iter().copied(), RankMethod::Average, false, pct)
+        }
+        CrossSection::Demean => values.iter().map(|v| v - mean).collect(),
+        CrossSection::ZScore => {
+            if n < 2 {
+                return vec![f64::NAN; values.len()];
+            }
+            let var = valid().fold(0., |acc, v| acc + (v - mean).powi(2)) / (n - 1) as f64;
+            let std = var.sqrt();
+            if std == 0. {
+                return vec![f64::NAN; values.len()];
+            }
+            values.iter().map(|v| (v - mean) / std).collect()
+        }
+        CrossSection::Scale => divide(valid().fold(0., |acc, v| acc + v.abs())),
+        CrossSection::Normalize => divide(sum),
+        CrossSection::Winsorize(method) => winsorize(values, method),
+    }
+}
+
+/// subtract the mean of each group from the values, `groups` are the group ids
+/// of the values. NaN values are excluded from the means and are kept as NaN,
+/// the values without a group are NaN.
+pub fn group_demean(values: &[f64], groups: &[Option<usize>]) -> Vec<f64> {
+    let n_groups = Iterator::max(groups.iter().flatten()).map_or(0, |g| g + 1);
+    let (mut count, mut sum) = (vec![0usize; n_groups], vec![0.; n_groups]);
+    for (v, g) in values.iter().zip(groups) {
+        if let (false, Some(g)) = (v.is_nan(), g) {
+            count[*g] += 1;
+            sum[*g] += v;
+        }
+    }
+    values
+        .iter()
+        .zip(groups)
+        .map(|(v, g)| g.map_or(f64::NAN, |g| v - sum[g] / count[g] as f64))
+        .collect()
+}
+
+/// residual of the ordinary least squares regression of the values on the
+/// exposures with an intercept. The rows where the value or any exposure is NaN
+/// are excluded from the regression and are kept as NaN, the result is NaN if
+/// there are not enough rows or the exposures are collinear.
+pub fn regress_residual(values: &[f64], exposures: &[&[f64]]) -> Vec<f64> {
+    let k = exposures.len();
+    let valid: Vec<bool> = (0..values.len())
+        .map(|i| !values[i].is_nan() && Iterator::all(&mut exposures.iter(), |e| !e[i].is_nan()))
+        .collect();
+    let n = valid.iter().filter(|v| **v).count();
+    let mut out = vec![f64::NAN; values.len()];
+    if n <= k {
+        return out;
+    }
+    let valid_mean = |v: &[f64]| {
+        v.iter()
+            .zip(&valid)
+            .filter_map(|(v, ok)| ok.then_some(v))
+            .fold(0., |acc, v| acc + v)
+            / n as f64
+    };
+    // regress on the centered data, so the intercept is implied
+    let y_mean = valid_mean(values);
+    let x_mean: Vec<f64> = exposures.iter().map(|e| valid_mean(e)).collect();
+    let x = |j: usize, i: usize| exposures[j][i] - x_mean[j];
+    // the normal equations `(X'X) beta = X'y` as an augmented matrix
+    let mut a = vec![vec![0.; k + 1]; k];
+    for i in (0..values.len()).filter(|i| valid[*i]) {
+        for (j, row) in a.iter_mut().enumerate() {
+            for (l, cell) in row[..k].iter_mut().enumerate() {
+                *cell += x(j, i) * x(l, i);
+            }
+            row[k] += x(j, i) * (values[i] - y_mean);
+        }
+    }
+    let scale = (0..k).fold(0., |acc: f64, j| acc.max(a[j][j]));
+    // gaussian elimination with partial pivoting
+    for c in 0..k {
+        let p = (c..k)
+            .max_by(|i, j| a[*i][c].abs().total_cmp(&a[*j][c].abs()))
+            .unwrap();
+        if a[p][c].abs() <= 1e-10 * scale {
+            return out;
+        }
+        a.swap(c, p);
+        let pivot = a[c].clone();
+        for (r, row) in a.iter_mut().enumerate() {
+            if r != c {
+                let factor = row[c] / pivot[c];
+                for (cell, p) in row[c..].iter_mut().zip(&pivot[c..]) {
+                    *cell -= factor * p;
+                }
+            }
+        }
+    }
+    let beta: Vec<f64> = (0..k).map(|j| a[j][k] / a[j][j]).collect();
+    for i in (0..values.len()).filter(|i| valid[*i]) {
+        let fitted = (0..k).fold(0., |acc, j| acc + beta[j] * x(j, i));
+        out[i] = values[i] - y_mean - fitted;
+    }
+    out
+}
+
+/// apply `f` on each lane along `axis` of `x` together with the lanes of
+/// `others` at the same position, an array in `others` can also be a 1-D array
+/// which is aligned with every lane.
+fn zip_lanes_apply<F>(
+    x: ArrayD<f64>,
+    others: Vec<ArrayD<f64>>,
+    axis: Option<usize>,
+    par: Option<bool>,
+    f: F,
+) -> TResult<ArrayD<f64>>
+where
+    F: Fn(&[f64], &[&[f64]]) -> Vec<f64> + Send + Sync,
+{
+    let ndim = x.ndim();
+    let axis = Axis(axis.unwrap_or(ndim.max(1) - 1));
+    tensure!(
+        axis.0 < ndim,
+        "axis {} is out of bounds for array of dimension {}",
+        axis.0,
+        ndim
+    );
+    let lane_len = x.shape()[axis.0];
+    let others = others
+        .into_iter()
+        .map(|o| {
+            if o.ndim() == 1 && o.len() == lane_len {
+                Ok(vec![o.iter().copied().collect()])
+            } else {
+                tensure!(
+                    o.shape() == x.shape(),
+                    "the shape of the other array should be the same or 1-D, got {:?} and {:?}",
+                    x.shape(),
+                    o.shape()
+                );
+                Ok(o.lanes(axis).into_iter().map(|l| l.to_vec()).collect())
+            }
+        })
+        .collect::<TResult<Vec<Vec<Vec<f64>>>>>()?;
+    let lanes: Vec<Vec<f64>> = x.lanes(axis).into_iter().map(|l| l.to_vec()).collect();
+    let apply = |i: usize| {
+        let others: Vec<&[f64]> = others
+            .iter()
+            .map(|o| o[if o.len() == 1 { 0 } else { i }].as_slice())
+            .collect();
+        f(&lanes[i], &others)
+    };
+    let res: Vec<Vec<f64>> = if par.unwrap_or(false) {
+        (0..lanes.len()).into_par_iter().map(apply).collect()
+    } else {
+        (0..lanes.len()).map(apply).collect()
+    };
+    let mut out = x;
+    for (mut o, r) in out.lanes_mut(axis).into_iter().zip(res) {
+        o.iter_mut().zip(r).for_each(|(o, r)| *o = r);
+    }
+    Ok(out)
+}
+
+impl<'a> DynArray<'a> {
+    /// transform the cross section of each lane along `axis`, which defaults to
+    /// the last axis so that each row of a time x asset panel is transformed.
+    pub fn cross_section(
+        &self,
+        op: CrossSection,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        op.check()?;
+        let ndim = self.ndim();
+        let axis = axis.unwrap_or(ndim.max(1) - 1);
+        tensure!(
+            axis < ndim,
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            ndim
+        );
+        Ok(self.to_f64_array()?.calc_map_trust_iter_func(
+            |lane| {
+                let lane = lane.iter().copied().collect::<Vec<_>>();
+                Box::new(cross_section(&lane, op).into_iter())
+            },
+            Some(axis),
+            par,
+        ))
+    }
+
+    /// subtract the mean of each group in the cross section of each lane along
+    /// `axis`, see [`DynArray::cross_section`] for the axis. The group labels
+    /// should be numeric, they can be an array of the same shape or a 1-D
+    /// array aligned with every lane. The values with a NaN label are NaN.
+    pub fn neutralize(
+        &self,
+        groups: &DynArray,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        zip_lanes_apply(
+            self.to_f64_array()?,
+            vec![groups.to_f64_array()?],
+            axis,
+            par,
+            |values, groups| {
+                let (ids, _) = hash_group_ids(groups[0]);
+                let ids: Vec<Option<usize>> = ids
+                    .into_iter()
+                    .zip(groups[0])
+                    .map(|(id, label)| (!label.is_nan()).then_some(id))
+                    .collect();
+                group_demean(values, &ids)
+            },
+        )
+    }
+
+    /// residual of the regression of the cross section of each lane along `axis`
+    /// on the exposures, see [`DynArray::neutralize`] for the shape of the exposures.
+    pub fn neutralize_regress(
+        &self,
+        exposures: &[DynArray],
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let exposures = exposures
+            .iter()
+            .map(|e| e.to_f64_array())
+            .collect::<TResult<Vec<_>>>()?;
+        zip_lanes_apply(self.to_f64_array()?, exposures, axis, par, regress_residual)
+    }
+}
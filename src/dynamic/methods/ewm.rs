@@ -0,0 +1,409 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use tevec::ndarray::{ArrayD, ArrayView1, ArrayViewMut1, Axis, Zip};
+
+/// How the decay of the exponential weights is specified, the same as pandas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EwmDecay {
+    /// `alpha = 2 / (span + 1)`, span should be greater than or equal to 1
+    Span(f64),
+    /// `alpha = 1 - exp(-ln(2) / halflife)`, halflife should be greater than 0
+    HalfLife(f64),
+    /// center of mass, `alpha = 1 / (1 + com)`, com should be greater than or equal to 0
+    Com(f64),
+    /// the smoothing factor, alpha should be in `(0, 1]`
+    Alpha(f64),
+}
+
+impl EwmDecay {
+    pub fn alpha(&self) -> TResult<f64> {
+        match *self {
+            EwmDecay::Span(span) => {
+                tensure!(span >= 1., "span should be greater than or equal to 1");
+                Ok(2. / (span + 1.))
+            }
+            EwmDecay::HalfLife(halflife) => {
+                tensure!(halflife > 0., "halflife should be greater than 0");
+                Ok(1. - (-std::f64::consts::LN_2 / halflife).exp())
+            }
+            EwmDecay::Com(com) => {
+                tensure!(com >= 0., "com should be greater than or equal to 0");
+                Ok(1. / (1. + com))
+            }
+            EwmDecay::Alpha(alpha) => {
+                tensure!(alpha > 0. && alpha <= 1., "alpha should be in (0, 1]");
+                Ok(alpha)
+            }
+        }
+    }
+}
+
+/// Exponentially weighted statistics of a single series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EwmAgg {
+    Mean,
+    Std,
+    Var,
+}
+
+impl EwmAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            EwmAgg::Mean => "ewm_mean",
+            EwmAgg::Std => "ewm_std",
+            EwmAgg::Var => "ewm_var",
+        }
+    }
+}
+
+/// Exponentially weighted statistics of two aligned series
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EwmPairAgg {
+    Corr,
+    Cov,
+}
+
+impl EwmPairAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            EwmPairAgg::Corr => "ewm_corr",
+            EwmPairAgg::Cov => "ewm_cov",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EwmOptions {
+    pub alpha: f64,
+    /// divide by the decaying adjustment factor in the beginning periods
+    pub adjust: bool,
+    /// ignore none values when calculating the weights
+    pub ignore_nulls: bool,
+    /// minimum number of observations required to have a value, at least 1
+    pub min_periods: usize,
+}
+
+impl EwmOptions {
+    #[inline]
+    pub fn new(decay: EwmDecay) -> TResult<Self> {
+        Ok(EwmOptions {
+            alpha: decay.alpha()?,
+            adjust: true,
+            ignore_nulls: false,
+            min_periods: 1,
+        })
+    }
+
+    #[inline]
+    fn new_wt(&self) -> f64 {
+        if self.adjust {
+            1.
+        } else {
+            self.alpha
+        }
+    }
+}
+
+/// the state of the exponentially weighted mean, the update rule is the same as pandas
+struct EwmMeanState {
+    opt: EwmOptions,
+    weighted: f64,
+    old_wt: f64,
+    nobs: usize,
+}
+
+impl EwmMeanState {
+    #[inline]
+    fn new(opt: EwmOptions) -> Self {
+        EwmMeanState {
+            opt,
+            weighted: f64::NAN,
+            old_wt: 1.,
+            nobs: 0,
+        }
+    }
+
+    fn update(&mut self, cur: f64) -> f64 {
+        let is_obs = !cur.is_nan();
+        self.nobs += is_obs as usize;
+        if !self.weighted.is_nan() {
+            if is_obs || !self.opt.ignore_nulls {
+                self.old_wt *= 1. - self.opt.alpha;
+                if is_obs {
+                    let new_wt = self.opt.new_wt();
+                    // avoid numerical errors on constant series
+                    if self.weighted != cur {
+                        self.weighted =
+                            (self.old_wt * self.weighted + new_wt * cur) / (self.old_wt + new_wt);
+                    }
+                    if self.opt.adjust {
+                        self.old_wt += new_wt;
+                    } else {
+                        self.old_wt = 1.;
+                    }
+                }
+            }
+        } else if is_obs {
+            self.weighted = cur;
+        }
+        if self.nobs >= self.opt.min_periods.max(1) {
+            self.weighted
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+/// the state of the exponentially weighted covariance, the update rule is the
+/// same as pandas, the covariance is unbiased unless `bias` is true
+struct EwmCovState {
+    opt: EwmOptions,
+    bias: bool,
+    mean_x: f64,
+    mean_y: f64,
+    cov: f64,
+    sum_wt: f64,
+    sum_wt2: f64,
+    old_wt: f64,
+    nobs: usize,
+}
+
+impl EwmCovState {
+    #[inline]
+    fn new(opt: EwmOptions, bias: bool) -> Self {
+        EwmCovState {
+            opt,
+            bias,
+            mean_x: f64::NAN,
+            mean_y: f64::NAN,
+            cov: 0.,
+            sum_wt: 1.,
+            sum_wt2: 1.,
+            old_wt: 1.,
+            nobs: 0,
+        }
+    }
+
+    fn update(&mut self, x: f64, y: f64) -> f64 {
+        let is_obs = !x.is_nan() && !y.is_nan();
+        self.nobs += is_obs as usize;
+        if !self.mean_x.is_nan() {
+            if is_obs || !self.opt.ignore_nulls {
+                let factor = 1. - self.opt.alpha;
+                self.sum_wt *= factor;
+                self.sum_wt2 *= factor * factor;
+                self.old_wt *= factor;
+                if is_obs {
+                    let (old_mean_x, old_mean_y) = (self.mean_x, self.mean_y);
+                    let (old_wt, new_wt) = (self.old_wt, self.opt.new_wt());
+                    // avoid numerical errors on constant series
+                    if self.mean_x != x {
+                        self.mean_x = (old_wt * old_mean_x + new_wt * x) / (old_wt + new_wt);
+                    }
+                    if self.mean_y != y {
+                        self.mean_y = (old_wt * old_mean_y + new_wt * y) / (old_wt + new_wt);
+                    }
+                    self.cov = (old_wt
+                        * (self.cov + (old_mean_x - self.mean_x) * (old_mean_y - self.mean_y))
+                        + new_wt * (x - self.mean_x) * (y - self.mean_y))
+                        / (old_wt + new_wt);
+                    self.sum_wt += new_wt;
+                    self.sum_wt2 += new_wt * new_wt;
+                    self.old_wt += new_wt;
+                    if !self.opt.adjust {
+                        self.sum_wt /= self.old_wt;
+                        self.sum_wt2 /= self.old_wt * self.old_wt;
+                        self.old_wt = 1.;
+                    }
+                }
+            }
+        } else if is_obs {
+            self.mean_x = x;
+            self.mean_y = y;
+        }
+        if self.nobs < self.opt.min_periods.max(1) {
+            return f64::NAN;
+        }
+        if self.bias {
+            return self.cov;
+        }
+        let numerator = self.sum_wt * self.sum_wt;
+        let denominator = numerator - self.sum_wt2;
+        if denominator > 0. {
+            numerator / denominator * self.cov
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+/// lazily calculate the exponentially weighted statistic, NaN values are none
+pub fn ewm_agg<I: Iterator<Item = f64>>(
+    iter: I,
+    agg: EwmAgg,
+    opt: EwmOptions,
+) -> impl Iterator<Item = f64> {
+    let mut mean = EwmMeanState::new(opt);
+    let mut cov = EwmCovState::new(opt, false);
+    iter.map(move |v| match agg {
+        EwmAgg::Mean => mean.update(v),
+        EwmAgg::Var => cov.update(v, v),
+        EwmAgg::Std => cov.update(v, v).sqrt(),
+    })
+}
+
+/// lazily calculate the exponentially weighted statistic of two aligned series,
+/// NaN values are none and the rows where either value is none are ignored.
+pub fn ewm_pair_agg<I: Iterator<Item = (f64, f64)>>(
+    iter: I,
+    agg: EwmPairAgg,
+    opt: EwmOptions,
+) -> impl Iterator<Item = f64> {
+    let bias = agg == EwmPairAgg::Corr;
+    let mut cov = EwmCovState::new(opt, bias);
+    let mut var_x = EwmCovState::new(opt, true);
+    let mut var_y = EwmCovState::new(opt, true);
+    iter.map(move |(x, y)| {
+        let (x, y) = if x.is_nan() || y.is_nan() {
+            (f64::NAN, f64::NAN)
+        } else {
+            (x, y)
+        };
+        let cov = cov.update(x, y);
+        match agg {
+            EwmPairAgg::Cov => cov,
+            EwmPairAgg::Corr => {
+                let var = var_x.update(x, x) * var_y.update(y, y);
+                if var > EPS {
+                    cov / var.sqrt()
+                } else {
+                    f64::NAN
+                }
+            }
+        }
+    })
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// lazily cast the values to float, none values are NaN
+    pub fn into_f64_iter(self) -> TResult<Box<dyn TrustedLen<Item = f64> + 'a>> {
+        match_trust_iter!(self; Numeric(e) => {
+            let len = e.len();
+            let iter: Box<dyn TrustedLen<Item = f64> + 'a> =
+                Box::new(e.map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64())).to_trust(len));
+            Ok(iter)
+        },)
+    }
+
+    /// lazily calculate the exponentially weighted statistic
+    pub fn ewm(self, agg: EwmAgg, opt: EwmOptions) -> TResult<DynTrustIter<'a>> {
+        let len = self.len();
+        let iter = self.into_f64_iter()?;
+        Ok(ewm_agg(iter, agg, opt).to_trust(len).into())
+    }
+
+    /// lazily calculate the exponentially weighted statistic with `other`
+    pub fn ewm_pair(
+        self,
+        other: DynTrustIter<'a>,
+        agg: EwmPairAgg,
+        opt: EwmOptions,
+    ) -> TResult<DynTrustIter<'a>> {
+        tensure!(self.len() == other.len(), lm, self.len(), other.len());
+        let len = self.len();
+        let iter = self.into_f64_iter()?.zip(other.into_f64_iter()?);
+        Ok(ewm_pair_agg(iter, agg, opt).to_trust(len).into())
+    }
+}
+
+impl<'a> DynArray<'a> {
+    /// calculate the exponentially weighted statistic on each lane along `axis`
+    pub fn ewm(
+        &self,
+        agg: EwmAgg,
+        opt: EwmOptions,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let axis = axis.unwrap_or(0);
+        tensure!(
+            axis < self.ndim(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            self.ndim()
+        );
+        match_array!(self; Numeric(a) => {
+            Ok(a.view().calc_map_trust_iter_func(
+                |lane| {
+                    let len = lane.len();
+                    let iter = lane
+                        .into_iter()
+                        .cloned()
+                        .map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64()));
+                    Box::new(ewm_agg(iter, agg, opt).to_trust(len))
+                },
+                Some(axis),
+                par,
+            ))
+        },)
+    }
+
+    /// calculate the exponentially weighted statistic of each lane along `axis` with
+    /// `other`, `other` can be a 1-D array which is aligned with every lane or an
+    /// array of the same shape.
+    pub fn ewm_pair(
+        &self,
+        other: &DynArray,
+        agg: EwmPairAgg,
+        opt: EwmOptions,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let axis = axis.unwrap_or(0);
+        let shape = self.shape();
+        tensure!(
+            axis < shape.len(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            shape.len()
+        );
+        let x = self.to_f64_array()?;
+        let y = other.to_f64_array()?;
+        if y.ndim() == 1 {
+            tensure!(y.len() == shape[axis], lm, y.len(), shape[axis]);
+            let y: Vec<f64> = y.iter().copied().collect();
+            return Ok(x.calc_map_trust_iter_func(
+                |lane| {
+                    let len = lane.len();
+                    let iter = lane.into_iter().copied().zip(y.iter().copied());
+                    Box::new(ewm_pair_agg(iter, agg, opt).to_trust(len))
+                },
+                Some(axis),
+                par,
+            ));
+        }
+        tensure!(
+            y.shape() == x.shape(),
+            "the shape of the other array should be the same or 1-D, got {:?} and {:?}",
+            x.shape(),
+            y.shape()
+        );
+        let mut out = ArrayD::<f64>::zeros(x.raw_dim());
+        let axis = Axis(axis);
+        let zip = Zip::from(out.lanes_mut(axis))
+            .and(x.lanes(axis))
+            .and(y.lanes(axis));
+        let f = |mut o: ArrayViewMut1<f64>, x: ArrayView1<f64>, y: ArrayView1<f64>| {
+            let res = ewm_pair_agg(x.into_iter().copied().zip(y.into_iter().copied()), agg, opt);
+            o.iter_mut().zip(res).for_each(|(o, r)| *o = r);
+        };
+        if par.unwrap_or(false) {
+            zip.par_for_each(f);
+        } else {
+            zip.for_each(f);
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,331 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+
+/// apply `f` to the inner value, none values are kept as none
+#[inline]
+fn vmap<T: IsNone, U: IsNone<Inner = U>>(v: T, f: impl FnOnce(T::Inner) -> U) -> T::Cast<U> {
+    T::Cast::<U>::from_opt(v.to_opt().map(f))
+}
+
+/// return 1 for positive values, -1 for negative values, zero and NaN are kept
+#[inline]
+fn sign<T: Number>(v: T) -> T {
+    if v > T::zero() {
+        T::one()
+    } else if v < T::zero() {
+        T::zero() - T::one()
+    } else {
+        v
+    }
+}
+
+#[inline]
+fn round_to<T: Number>(v: T, decimals: u32) -> T
+where
+    f64: Cast<T>,
+{
+    let scale = 10f64.powi(decimals as i32);
+    ((v.f64() * scale).round() / scale).cast()
+}
+
+#[inline]
+fn clip<T: PartialOrd>(v: T, lower: Option<T>, upper: Option<T>) -> T {
+    match (lower, upper) {
+        (Some(lower), _) if v < lower => lower,
+        (_, Some(upper)) if v > upper => upper,
+        _ => v,
+    }
+}
+
+/// the lower bound of integers, rounded up
+#[inline]
+fn int_lower<T: Number>(lower: Option<f64>) -> Option<T>
+where
+    f64: Cast<T>,
+{
+    lower.map(|v| v.ceil().cast())
+}
+
+/// the upper bound of integers, rounded down
+#[inline]
+fn int_upper<T: Number>(upper: Option<f64>) -> Option<T>
+where
+    f64: Cast<T>,
+{
+    upper.map(|v| v.floor().cast())
+}
+
+/// generate an elementwise function for both `DynTrustIter` and `DynArray`,
+/// the function is lazy for `DynTrustIter`
+macro_rules! impl_map {
+    ($(
+        $(#[$meta: meta])*
+        $func: ident($($arg: ident: $ty: ty),*) {
+            $($group: ident($v: ident) => $body: expr),+ $(,)?
+        }
+    )*) => {
+        impl<'a> DynTrustIter<'a> {
+            $(
+                $(#[$meta])*
+                #[inline]
+                pub fn $func(self, $($arg: $ty),*) -> TResult<Self> {
+                    match_trust_iter!(self; $($group(e) => Ok(e.map(move |$v| $body).into()),)+)
+                }
+            )*
+        }
+
+        impl<'a> DynArray<'a> {
+            $(
+                $(#[$meta])*
+                #[inline]
+                pub fn $func<'b>(&self, $($arg: $ty),*) -> TResult<DynArray<'b>> {
+                    match_array!(self; $($group(a) => {
+                        Ok(a.view().map(move |$v| {
+                            let $v = *$v;
+                            $body
+                        }).into())
+                    },)+)
+                }
+            )*
+        }
+    };
+}
+
+/// generate functions that promote the values to float,
+/// functions prefixed with `v` keep none values as none
+macro_rules! impl_float_map {
+    ($($func: ident, $vfunc: ident, $op: ident);* $(;)?) => {
+        impl_map!(
+            $(
+                $func() {
+                    PureFloat(v) => v.$op(),
+                    PureInt(v) => v.f64().$op(),
+                }
+
+                $vfunc() {
+                    Float(v) => vmap(v, |x| x.$op()),
+                    Int(v) => vmap(v, |x| x.f64().$op()),
+                }
+            )*
+        );
+    };
+}
+
+impl_float_map!(
+    sqrt, vsqrt, sqrt;
+    cbrt, vcbrt, cbrt;
+    exp, vexp, exp;
+    log1p, vlog1p, ln_1p;
+    sin, vsin, sin;
+    cos, vcos, cos;
+    tan, vtan, tan;
+    asin, vasin, asin;
+    acos, vacos, acos;
+    atan, vatan, atan;
+    sinh, vsinh, sinh;
+    cosh, vcosh, cosh;
+    tanh, vtanh, tanh;
+);
+
+impl_map!(
+    /// logarithm with respect to `base`, natural logarithm if `base` is none
+    log(base: Option<f64>) {
+        PureFloat(v) => match base {
+            Some(base) => v.log(base.cast()),
+            None => v.ln(),
+        },
+        PureInt(v) => match base {
+            Some(base) => v.f64().log(base),
+            None => v.f64().ln(),
+        },
+    }
+
+    vlog(base: Option<f64>) {
+        Float(v) => vmap(v, |x| match base {
+            Some(base) => x.log(base.cast()),
+            None => x.ln(),
+        }),
+        Int(v) => vmap(v, |x| match base {
+            Some(base) => x.f64().log(base),
+            None => x.f64().ln(),
+        }),
+    }
+
+    sign() {
+        PureNumeric(v) => sign(v),
+    }
+
+    vsign() {
+        Numeric(v) => vmap(v, sign),
+    }
+
+    floor() {
+        PureFloat(v) => v.floor(),
+        PureInt(v) => v,
+    }
+
+    vfloor() {
+        Float(v) => vmap(v, |x| x.floor()),
+        Int(v) => v,
+    }
+
+    ceil() {
+        PureFloat(v) => v.ceil(),
+        PureInt(v) => v,
+    }
+
+    vceil() {
+        Float(v) => vmap(v, |x| x.ceil()),
+        Int(v) => v,
+    }
+
+    /// round the values to `decimals` decimal places, integers are kept
+    round(decimals: u32) {
+        PureFloat(v) => round_to(v, decimals),
+        PureInt(v) => v,
+    }
+
+    vround(decimals: u32) {
+        Float(v) => vmap(v, |x| round_to(x, decimals)),
+        Int(v) => v,
+    }
+
+    /// raise the values to a float power, integers are promoted to float
+    powf(exponent: f64) {
+        PureFloat(v) => v.powf(exponent.cast()),
+        PureInt(v) => v.f64().powf(exponent),
+    }
+
+    vpowf(exponent: f64) {
+        Float(v) => vmap(v, |x| x.powf(exponent.cast())),
+        Int(v) => vmap(v, |x| x.f64().powf(exponent)),
+    }
+
+    /// limit the values to `[lower, upper]`, NaN values are kept. For integers
+    /// the bounds are rounded inwards so that the result stays in the range
+    clip(lower: Option<f64>, upper: Option<f64>) {
+        PureFloat(v) => clip(v, lower.map(Cast::cast), upper.map(Cast::cast)),
+        PureInt(v) => clip(v, int_lower(lower), int_upper(upper)),
+    }
+
+    vclip(lower: Option<f64>, upper: Option<f64>) {
+        Float(v) => vmap(v, |x| clip(x, lower.map(Cast::cast), upper.map(Cast::cast))),
+        Int(v) => vmap(v, |x| clip(x, int_lower(lower), int_upper(upper))),
+    }
+);
+
+/// get the integer exponent if the scalar is a non-negative integer
+#[inline]
+fn int_exponent(exponent: &Scalar) -> Option<u32> {
+    match_scalar!(exponent; PureInt(v) => Ok(v.i64()),)
+        .ok()
+        .filter(|n| (0..=u32::MAX as i64).contains(n))
+        .map(|n| n as u32)
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// raise the values to a non-negative integer power, integers are kept and
+    /// an error is returned if the power of an integer overflows
+    pub fn powi(self, n: u32) -> TResult<Self> {
+        match_trust_iter!(self;
+            PureFloat(e) => Ok(e.map(move |v| v.powi(n as i32)).into()),
+            PureInt(e) => {
+                let mut overflow = false;
+                let out: DynVec = e
+                    .map(|v| v.checked_pow(n).unwrap_or_else(|| {
+                        overflow = true;
+                        v
+                    }))
+                    .collect_trusted_to_vec()
+                    .into();
+                tensure!(!overflow, "integer overflow when raising to the power of {}", n);
+                out.into_titer()
+            },
+        )
+    }
+
+    pub fn vpowi(self, n: u32) -> TResult<Self> {
+        match_trust_iter!(self;
+            Float(e) => Ok(e.map(move |v| vmap(v, |x| x.powi(n as i32))).into()),
+            Int(e) => {
+                let mut overflow = false;
+                let out: DynVec = e
+                    .map(|v| vmap(v, |x| x.checked_pow(n).unwrap_or_else(|| {
+                        overflow = true;
+                        x
+                    })))
+                    .collect_trusted_to_vec()
+                    .into();
+                tensure!(!overflow, "integer overflow when raising to the power of {}", n);
+                out.into_titer()
+            },
+        )
+    }
+
+    /// raise the values to the power of `exponent`, integers are kept if
+    /// `exponent` is a non-negative integer, otherwise they are promoted to float
+    #[inline]
+    pub fn pow(self, exponent: Scalar) -> TResult<Self> {
+        match int_exponent(&exponent) {
+            Some(n) => self.powi(n),
+            None => self.powf(exponent.cast_f64()?),
+        }
+    }
+
+    #[inline]
+    pub fn vpow(self, exponent: Scalar) -> TResult<Self> {
+        match int_exponent(&exponent) {
+            Some(n) => self.vpowi(n),
+            None => self.vpowf(exponent.cast_f64()?),
+        }
+    }
+}
+
+impl<'a> DynArray<'a> {
+    /// see [`DynTrustIter::powi`]
+    pub fn powi<'b>(&self, n: u32) -> TResult<DynArray<'b>> {
+        match_array!(self;
+            PureFloat(a) => Ok(a.view().map(move |v| v.powi(n as i32)).into()),
+            PureInt(a) => {
+                let mut overflow = false;
+                let out = a.view().map(|v| v.checked_pow(n).unwrap_or_else(|| {
+                    overflow = true;
+                    *v
+                }));
+                tensure!(!overflow, "integer overflow when raising to the power of {}", n);
+                Ok(out.into())
+            },
+        )
+    }
+
+    pub fn vpowi<'b>(&self, n: u32) -> TResult<DynArray<'b>> {
+        match_array!(self;
+            Float(a) => Ok(a.view().map(move |v| vmap(*v, |x| x.powi(n as i32))).into()),
+            Int(a) => {
+                let mut overflow = false;
+                let out = a.view().map(|v| vmap(*v, |x| x.checked_pow(n).unwrap_or_else(|| {
+                    overflow = true;
+                    x
+                })));
+                tensure!(!overflow, "integer overflow when raising to the power of {}", n);
+                Ok(out.into())
+            },
+        )
+    }
+
+    #[inline]
+    pub fn pow<'b>(&self, exponent: Scalar) -> TResult<DynArray<'b>> {
+        match int_exponent(&exponent) {
+            Some(n) => self.powi(n),
+            None => self.powf(exponent.cast_f64()?),
+        }
+    }
+
+    #[inline]
+    pub fn vpow<'b>(&self, exponent: Scalar) -> TResult<DynArray<'b>> {
+        match int_exponent(&exponent) {
+            Some(n) => self.vpowi(n),
+            None => self.vpowf(exponent.cast_f64()?),
+        }
+    }
+}
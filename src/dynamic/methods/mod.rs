@@ -1,4 +1,56 @@
 #[cfg(feature = "map")]
+mod cross_section;
+#[cfg(feature = "rolling")]
+mod ewm;
+#[cfg(feature = "map")]
 mod map;
+#[cfg(feature = "map")]
+mod math;
+#[cfg(feature = "map")]
+mod rank;
+#[cfg(feature = "rolling")]
+mod rolling;
+#[cfg(all(feature = "rolling", feature = "time"))]
+mod rolling_by;
+#[cfg(feature = "rolling")]
+mod rolling_order;
+#[cfg(feature = "rolling")]
+mod rolling_weighted;
+#[cfg(feature = "map")]
+mod select;
+#[cfg(feature = "map")]
+mod sort;
+#[cfg(feature = "map")]
+mod unique;
+#[cfg(feature = "map")]
+mod winsorize;
 
 mod agg;
+
+#[cfg(feature = "map")]
+pub use cross_section::{cross_section, group_demean, regress_residual, CrossSection};
+#[cfg(feature = "rolling")]
+pub use ewm::{ewm_agg, ewm_pair_agg, EwmAgg, EwmDecay, EwmOptions, EwmPairAgg};
+#[cfg(feature = "map")]
+pub use rank::{rank_by, RankMethod};
+#[cfg(all(feature = "rolling", feature = "pl"))]
+pub use rolling::series_rolling_agg;
+#[cfg(feature = "rolling")]
+pub use rolling::{
+    bounds_valid_count, center_offset, fixed_window_bounds, rolling_agg, rolling_pair_agg,
+    RollingAgg, RollingPairAgg,
+};
+#[cfg(all(feature = "rolling", feature = "time"))]
+pub use rolling_by::{rolling_agg_by, ClosedWindow, WindowLabel};
+#[cfg(feature = "rolling")]
+pub use rolling_order::{rolling_order_agg, RollingOrderAgg};
+#[cfg(feature = "rolling")]
+pub use rolling_weighted::{rolling_weighted_agg, WeightKernel, WeightedAgg};
+#[cfg(feature = "map")]
+pub use select::{resolve_index, slice_bound};
+#[cfg(feature = "map")]
+pub use sort::{arg_sort_by, arg_top_k_by, null_sort_cmp};
+#[cfg(feature = "map")]
+pub use unique::{group_counts, hash_group_ids, HashKey};
+#[cfg(feature = "map")]
+pub use winsorize::{winsorize, WinsorizeBounds};
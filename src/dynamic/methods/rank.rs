@@ -0,0 +1,99 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+/// How to assign ranks to a group of equal values
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RankMethod {
+    /// average rank of the group
+    #[default]
+    Average,
+    /// lowest rank in the group
+    Min,
+    /// highest rank in the group
+    Max,
+    /// like `Min`, but rank always increases by 1 between groups
+    Dense,
+    /// ranks are given in the order the values appear in the data
+    Ordinal,
+}
+
+impl std::str::FromStr for RankMethod {
+    type Err = TError;
+
+    #[inline]
+    fn from_str(s: &str) -> TResult<Self> {
+        match s {
+            "average" => Ok(RankMethod::Average),
+            "min" => Ok(RankMethod::Min),
+            "max" => Ok(RankMethod::Max),
+            "dense" => Ok(RankMethod::Dense),
+            "ordinal" | "first" => Ok(RankMethod::Ordinal),
+            _ => tbail!("unknown rank method: {}", s),
+        }
+    }
+}
+
+/// rank the values of the iterator, none values will be ranked as NaN
+///
+/// if `pct` is true, the rank is divided by the number of valid values (the
+/// number of distinct values for the dense method) so that the result lies
+/// in (0, 1]
+pub fn rank_by<I, T>(iter: I, method: RankMethod, descending: bool, pct: bool) -> Vec<f64>
+where
+    I: IntoIterator<Item = T>,
+    T: IsNone,
+    T::Inner: PartialOrd,
+{
+    let values: Vec<Option<T::Inner>> = iter.into_iter().map(IsNone::to_opt).collect();
+    let mut out = vec![f64::NAN; values.len()];
+    let mut idx_sorted: Vec<usize> = (0..values.len()).filter(|i| values[*i].is_some()).collect();
+    let cmp = |a: &usize, b: &usize| {
+        let (va, vb) = (values[*a].as_ref().unwrap(), values[*b].as_ref().unwrap());
+        let ord = va.partial_cmp(vb).unwrap_or(Ordering::Equal);
+        if descending {
+            ord.reverse()
+        } else {
+            ord
+        }
+    };
+    // stable sort is needed so that ordinal rank follows the order of appearance
+    idx_sorted.sort_by(cmp);
+    let n = idx_sorted.len();
+    let (mut start, mut dense) = (0, 0);
+    while start < n {
+        let mut end = start;
+        while end + 1 < n && cmp(&idx_sorted[start], &idx_sorted[end + 1]) == Ordering::Equal {
+            end += 1;
+        }
+        dense += 1;
+        for (k, idx) in idx_sorted[start..=end].iter().enumerate() {
+            out[*idx] = match method {
+                RankMethod::Average => (start + end + 2) as f64 / 2.,
+                RankMethod::Min => (start + 1) as f64,
+                RankMethod::Max => (end + 1) as f64,
+                RankMethod::Dense => dense as f64,
+                RankMethod::Ordinal => (start + k + 1) as f64,
+            };
+        }
+        start = end + 1;
+    }
+    if pct && n > 0 {
+        let max_rank = if method == RankMethod::Dense {
+            dense as f64
+        } else {
+            n as f64
+        };
+        out.iter_mut().for_each(|v| *v /= max_rank);
+    }
+    out
+}
+
+impl<'a> DynTrustIter<'a> {
+    #[inline]
+    pub fn rank(self, method: RankMethod, descending: bool, pct: bool) -> TResult<Self> {
+        match_trust_iter!(self; (Normal | String | Time)(e) => {
+            Ok(rank_by(e, method, descending, pct).into_iter().into())
+        },)
+    }
+}
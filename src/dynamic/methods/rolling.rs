@@ -0,0 +1,618 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use tevec::ndarray::{ArrayD, ArrayView1, ArrayViewMut1, Axis, Zip};
+#[cfg(feature = "pl")]
+use tevec::polars::prelude::Series;
+
+/// Aggregations that can be calculated incrementally on a rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollingAgg {
+    Sum,
+    Mean,
+    Std,
+    Var,
+    Min,
+    Max,
+    Skew,
+    Kurt,
+    Count,
+    Median,
+}
+
+impl RollingAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            RollingAgg::Sum => "rolling_sum",
+            RollingAgg::Mean => "rolling_mean",
+            RollingAgg::Std => "rolling_std",
+            RollingAgg::Var => "rolling_var",
+            RollingAgg::Min => "rolling_min",
+            RollingAgg::Max => "rolling_max",
+            RollingAgg::Skew => "rolling_skew",
+            RollingAgg::Kurt => "rolling_kurt",
+            RollingAgg::Count => "rolling_count",
+            RollingAgg::Median => "rolling_median",
+        }
+    }
+}
+
+/// Aggregations of two aligned series on a rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollingPairAgg {
+    Corr,
+    Cov,
+    /// the slope of regressing the first series on the second one
+    Beta,
+}
+
+impl RollingPairAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            RollingPairAgg::Corr => "rolling_corr",
+            RollingPairAgg::Cov => "rolling_cov",
+            RollingPairAgg::Beta => "rolling_beta",
+        }
+    }
+}
+
+/// the number of values the result should be shifted back so that
+/// the result is labeled at the center of the window
+#[inline]
+pub fn center_offset(window: usize, center: bool) -> usize {
+    if center {
+        (window - 1) / 2
+    } else {
+        0
+    }
+}
+
+/// get the bounds `[start, end)` of the fixed window of each row,
+/// the windows at the end are shrunk if `center` is true
+pub fn fixed_window_bounds(len: usize, window: usize, center: bool) -> Vec<(usize, usize)> {
+    let offset = center_offset(window, center);
+    (0..len)
+        .map(|i| {
+            let end = (i + offset + 1).min(len);
+            let start = (i + offset + 1).saturating_sub(window);
+            (start.min(end), end)
+        })
+        .collect()
+}
+
+/// count the valid rows in each window `[start, end)`
+pub fn bounds_valid_count(valid: &[bool], bounds: &[(usize, usize)]) -> Vec<usize> {
+    let mut n = 0;
+    let cum_valid: Vec<usize> = std::iter::once(0)
+        .chain(valid.iter().map(|v| {
+            n += *v as usize;
+            n
+        }))
+        .collect();
+    bounds
+        .iter()
+        .map(|(start, end)| cum_valid[*end] - cum_valid[*start])
+        .collect()
+}
+
+/// the co-moments of the valid pairs in a window, they are updated in the
+/// Welford style so that they don't lose precision when the values are far
+/// from zero. The pairs with an infinite value are only counted, the result is
+/// NaN while they are in the window.
+#[derive(Default)]
+struct PairState {
+    n: usize,
+    non_finite: usize,
+    mean_x: f64,
+    mean_y: f64,
+    m_xx: f64,
+    m_yy: f64,
+    m_xy: f64,
+}
+
+impl PairState {
+    #[inline]
+    fn update(&mut self, x: f64, y: f64, add: bool) {
+        if x.is_nan() || y.is_nan() {
+            return;
+        }
+        if x.is_infinite() || y.is_infinite() {
+            if add {
+                self.non_finite += 1;
+            } else {
+                self.non_finite -= 1;
+            }
+            return;
+        }
+        if add {
+            self.n += 1;
+            let n = self.n as f64;
+            let dx = x - self.mean_x;
+            let dy = y - self.mean_y;
+            self.mean_x += dx / n;
+            self.mean_y += dy / n;
+            self.m_xx += dx * (x - self.mean_x);
+            self.m_yy += dy * (y - self.mean_y);
+            self.m_xy += dx * (y - self.mean_y);
+        } else {
+            self.n -= 1;
+            if self.n == 0 {
+                *self = PairState {
+                    non_finite: self.non_finite,
+                    ..Default::default()
+                };
+                return;
+            }
+            // the inverse of adding the pair to the rest of the window
+            let n = self.n as f64;
+            let mean_x = self.mean_x - (x - self.mean_x) / n;
+            let mean_y = self.mean_y - (y - self.mean_y) / n;
+            self.m_xx -= (x - mean_x) * (x - self.mean_x);
+            self.m_yy -= (y - mean_y) * (y - self.mean_y);
+            self.m_xy -= (x - mean_x) * (y - self.mean_y);
+            self.mean_x = mean_x;
+            self.mean_y = mean_y;
+        }
+    }
+
+    fn value(&self, agg: RollingPairAgg, min_periods: usize) -> f64 {
+        if self.n + self.non_finite < min_periods.max(2) || self.non_finite > 0 {
+            return f64::NAN;
+        }
+        let n = self.n as f64;
+        let cov = self.m_xy / (n - 1.);
+        let var_y = self.m_yy / (n - 1.);
+        match agg {
+            RollingPairAgg::Cov => cov,
+            RollingPairAgg::Beta => {
+                if var_y > EPS {
+                    cov / var_y
+                } else {
+                    f64::NAN
+                }
+            }
+            RollingPairAgg::Corr => {
+                let var_x = self.m_xx / (n - 1.);
+                if var_x > EPS && var_y > EPS {
+                    cov / (var_x * var_y).sqrt()
+                } else {
+                    f64::NAN
+                }
+            }
+        }
+    }
+}
+
+/// calculate the aggregation of `x` and `y` in each window `[start, end)`,
+/// the bounds should be monotonic so that the window can be updated incrementally.
+///
+/// `x`, `y` and `bounds` should have the same length, the rows where either
+/// value is NaN are ignored and at least 2 valid pairs are needed.
+pub fn rolling_pair_agg(
+    x: &[f64],
+    y: &[f64],
+    agg: RollingPairAgg,
+    bounds: &[(usize, usize)],
+    min_periods: Option<usize>,
+) -> Vec<f64> {
+    let min_periods = min_periods.unwrap_or(1);
+    let mut state = PairState::default();
+    let (mut start, mut end) = (0, 0);
+    bounds
+        .iter()
+        .map(|&(s, e)| {
+            while end < e {
+                state.update(x[end], y[end], true);
+                end += 1;
+            }
+            while start < s {
+                state.update(x[start], y[start], false);
+                start += 1;
+            }
+            state.value(agg, min_periods)
+        })
+        .collect()
+}
+
+/// count the valid values in each window
+fn ts_vcount<V: Vec1View<T>, T: IsNone>(
+    v: &V,
+    window: usize,
+    min_periods: Option<usize>,
+) -> Vec<f64> {
+    let min_periods = min_periods.unwrap_or(1).min(window);
+    let mut n = 0usize;
+    v.rolling_apply(
+        window,
+        move |v_rm, v| {
+            if v.not_none() {
+                n += 1;
+            }
+            let res = if n >= min_periods { n as f64 } else { f64::NAN };
+            if let Some(v_rm) = v_rm {
+                if v_rm.not_none() {
+                    n -= 1;
+                }
+            }
+            res
+        },
+        None,
+    )
+    .unwrap()
+}
+
+/// sum of each window in the dtype of the data. The running sum is compensated
+/// (Neumaier summation) so that small values are not lost after a large value
+/// leaves the window, and a window containing non-finite values is summed
+/// directly so that an inf does not poison the later windows.
+fn ts_sum<V: Vec1View<T>, T: IsNone>(v: &V, window: usize) -> Vec<T::Inner>
+where
+    T::Inner: Number,
+{
+    let values: Vec<Option<T::Inner>> = v.titer().map(IsNone::to_opt).collect();
+    let is_finite = |v: T::Inner| v.f64().is_finite();
+    let (mut sum, mut comp) = (T::Inner::zero(), T::Inner::zero());
+    let mut non_finite = 0usize;
+    (0..values.len())
+        .map(|i| {
+            if let Some(v) = values[i] {
+                if is_finite(v) {
+                    let t = sum + v;
+                    if sum.f64().abs() >= v.f64().abs() {
+                        comp += (sum - t) + v;
+                    } else {
+                        comp += (v - t) + sum;
+                    }
+                    sum = t;
+                } else {
+                    non_finite += 1;
+                }
+            }
+            let start = (i + 1).saturating_sub(window);
+            let res = if non_finite > 0 {
+                values[start..=i]
+                    .iter()
+                    .flatten()
+                    .fold(T::Inner::zero(), |acc, v| acc + *v)
+            } else {
+                sum + comp
+            };
+            if i + 1 >= window {
+                if let Some(v) = values[start] {
+                    if is_finite(v) {
+                        let t = sum - v;
+                        if sum.f64().abs() >= v.f64().abs() {
+                            comp += (sum - t) - v;
+                        } else {
+                            comp += sum - (t + v);
+                        }
+                        sum = t;
+                    } else {
+                        non_finite -= 1;
+                    }
+                }
+            }
+            res
+        })
+        .collect()
+}
+
+/// median of each window, the windows are kept in the order statistic tree of
+/// [`rolling_order_agg`] so that each step costs O(log window)
+fn ts_vmedian<V: Vec1View<T>, T: IsNone>(
+    v: &V,
+    window: usize,
+    min_periods: Option<usize>,
+) -> Vec<f64>
+where
+    T::Inner: Number,
+{
+    let min_periods = min_periods.unwrap_or(1).min(window).max(1);
+    let values: Vec<f64> = v
+        .titer()
+        .map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64()))
+        .collect();
+    let bounds = fixed_window_bounds(values.len(), window, false);
+    let agg = RollingOrderAgg::Quantile {
+        q: 0.5,
+        method: QuantileMethod::Linear,
+    };
+    rolling_order_agg(&values, agg, &bounds, Some(min_periods))
+}
+
+/// calculate the rolling aggregation, none values are ignored in each window
+/// and `min_periods` defaults to 1.
+///
+/// if `center` is true, the result is labeled at the center of the window
+pub fn rolling_agg<V: Vec1View<T>, T: IsNone>(
+    v: &V,
+    agg: RollingAgg,
+    window: usize,
+    min_periods: Option<usize>,
+    center: bool,
+) -> Vec<f64>
+where
+    T::Inner: Number,
+    Option<T::Inner>: Cast<f64>,
+{
+    let offset = center_offset(window, center);
+    if offset > 0 {
+        // pad none values so that the last windows are shrunk at the end
+        let padded: Vec<Option<T::Inner>> = v
+            .titer()
+            .map(IsNone::to_opt)
+            .chain(std::iter::repeat_n(None, offset))
+            .collect();
+        let mut out = trailing_rolling_agg(&padded, agg, window, min_periods);
+        out.drain(..offset);
+        out
+    } else {
+        trailing_rolling_agg(v, agg, window, min_periods)
+    }
+}
+
+fn trailing_rolling_agg<V: Vec1View<T>, T: IsNone>(
+    v: &V,
+    agg: RollingAgg,
+    window: usize,
+    min_periods: Option<usize>,
+) -> Vec<f64>
+where
+    T::Inner: Number,
+    Option<T::Inner>: Cast<f64>,
+{
+    match agg {
+        RollingAgg::Sum => v.ts_vsum(window, min_periods),
+        RollingAgg::Mean => v.ts_vmean(window, min_periods),
+        RollingAgg::Std => v.ts_vstd(window, min_periods),
+        RollingAgg::Var => v.ts_vvar(window, min_periods),
+        RollingAgg::Min => v.ts_vmin(window, min_periods),
+        RollingAgg::Max => v.ts_vmax(window, min_periods),
+        RollingAgg::Skew => v.ts_vskew(window, min_periods),
+        RollingAgg::Kurt => v.ts_vkurt(window, min_periods),
+        RollingAgg::Count => ts_vcount(v, window, min_periods),
+        RollingAgg::Median => ts_vmedian(v, window, min_periods),
+    }
+}
+
+impl<'a> DynVec<'a> {
+    /// get a view of the values in `[start, end)`
+    #[inline]
+    pub fn window_view(&self, start: usize, end: usize) -> TResult<DynVec<'_>> {
+        tensure!(start <= end && end <= self.len(), "window out of bounds");
+        match_vec!(self; Dynamic(v) => Ok(std::borrow::Cow::Borrowed(&v[start..end]).into()),)
+    }
+
+    #[inline]
+    pub fn rolling_agg(
+        &self,
+        agg: RollingAgg,
+        window: usize,
+        min_periods: Option<usize>,
+        center: bool,
+    ) -> TResult<Vec<f64>> {
+        tensure!(window > 0, "window should be greater than 0");
+        match_vec!(self; Numeric(v) => {
+            let view = ArrayView1::from(v.as_ref());
+            Ok(rolling_agg(&view, agg, window, min_periods, center))
+        },)
+    }
+}
+
+impl<'a> DynArray<'a> {
+    /// rolling aggregation on each lane along `axis`, the output has the same
+    /// shape as the array
+    #[inline]
+    pub fn rolling_agg(
+        &self,
+        agg: RollingAgg,
+        window: usize,
+        min_periods: Option<usize>,
+        center: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        tensure!(window > 0, "window should be greater than 0");
+        let axis = axis.unwrap_or(0);
+        tensure!(
+            axis < self.ndim(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            self.ndim()
+        );
+        match_array!(self; Numeric(a) => {
+            Ok(a.view().calc_map_trust_iter_func(
+                |lane| Box::new(rolling_agg(&lane, agg, window, min_periods, center).into_iter()),
+                Some(axis),
+                par,
+            ))
+        },)
+    }
+
+    /// aggregate each lane along `axis` with `other` in each window `[start, end)`,
+    /// `other` can be a 1-D array which is aligned with every lane or an array
+    /// of the same shape.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rolling_pair_agg(
+        &self,
+        other: &DynArray,
+        agg: RollingPairAgg,
+        bounds: &[(usize, usize)],
+        min_periods: Option<usize>,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let axis = axis.unwrap_or(0);
+        let shape = self.shape();
+        tensure!(
+            axis < shape.len(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            shape.len()
+        );
+        tensure!(shape[axis] == bounds.len(), lm, shape[axis], bounds.len());
+        let x = self.to_f64_array()?;
+        let y = other.to_f64_array()?;
+        if y.ndim() == 1 {
+            tensure!(y.len() == bounds.len(), lm, y.len(), bounds.len());
+            let y: Vec<f64> = y.iter().copied().collect();
+            return Ok(x.calc_map_trust_iter_func(
+                |lane| {
+                    let lane = lane.iter().copied().collect::<Vec<_>>();
+                    Box::new(rolling_pair_agg(&lane, &y, agg, bounds, min_periods).into_iter())
+                },
+                Some(axis),
+                par,
+            ));
+        }
+        tensure!(
+            y.shape() == x.shape(),
+            "the shape of the other array should be the same or 1-D, got {:?} and {:?}",
+            x.shape(),
+            y.shape()
+        );
+        let mut out = ArrayD::<f64>::zeros(x.raw_dim());
+        let axis = Axis(axis);
+        let zip = Zip::from(out.lanes_mut(axis))
+            .and(x.lanes(axis))
+            .and(y.lanes(axis));
+        let f = |mut o: ArrayViewMut1<f64>, x: ArrayView1<f64>, y: ArrayView1<f64>| {
+            let x = x.iter().copied().collect::<Vec<_>>();
+            let y = y.iter().copied().collect::<Vec<_>>();
+            let res = rolling_pair_agg(&x, &y, agg, bounds, min_periods);
+            o.iter_mut().zip(res).for_each(|(o, r)| *o = r);
+        };
+        if par.unwrap_or(false) {
+            zip.par_for_each(f);
+        } else {
+            zip.for_each(f);
+        }
+        Ok(out)
+    }
+}
+
+/// match the dtypes that can hold none values, ints and bools
+/// are promoted to the option dtype
+macro_rules! match_nullable {
+    ($iter: expr; $e: ident => $body: expr) => {
+        match_trust_iter!($iter;
+            (Float | OptInt | OptBool | Time)($e) => $body,
+            (I32 | I64 | Usize | Bool)($e) => {
+                let $e = $e.map(Some);
+                $body
+            },
+        )
+    };
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// lazily append `n` none values, ints and bools are promoted to the option dtype
+    pub fn pad_none(self, n: usize) -> TResult<Self> {
+        let len = self.len() + n;
+        match_nullable!(self; e => {
+            let pad = std::iter::repeat_with(IsNone::none).take(n);
+            Ok(e.chain(pad).to_trust(len).into())
+        })
+    }
+
+    /// lazily set the values to none where the mask is true,
+    /// ints and bools are promoted to the option dtype
+    pub fn mask_none(self, mask: Vec<bool>) -> TResult<Self> {
+        tensure!(mask.len() == self.len(), lm, mask.len(), self.len());
+        let len = mask.len();
+        match_nullable!(self; e => {
+            let iter = e.zip(mask).map(|(v, m)| if m { IsNone::none() } else { v });
+            Ok(iter.to_trust(len).into())
+        })
+    }
+
+    /// lazily keep every `step`-th value starting from the first one
+    pub fn step_by(self, step: usize) -> TResult<Self> {
+        tensure!(step > 0, "step should be greater than 0");
+        let len = self.len().div_ceil(step);
+        match_trust_iter!(self; Dynamic(e) => Ok(e.step_by(step).to_trust(len).into()),)
+    }
+
+    /// lazily replace the values which are not at a multiple of `step` with
+    /// the last value at a multiple of `step`
+    #[allow(clippy::clone_on_copy)]
+    pub fn step_ffill(self, step: usize) -> TResult<Self> {
+        tensure!(step > 0, "step should be greater than 0");
+        let len = self.len();
+        match_trust_iter!(self; Dynamic(e) => {
+            let mut last = None;
+            let iter = e.enumerate().map(move |(i, v)| {
+                if i % step == 0 {
+                    last = Some(v);
+                }
+                last.clone().unwrap()
+            });
+            Ok(iter.to_trust(len).into())
+        },)
+    }
+
+    /// lazily repeat each value `n` times and keep the first `len` values
+    #[allow(clippy::clone_on_copy)]
+    pub fn repeat_each(self, n: usize, len: usize) -> TResult<Self> {
+        tensure!(
+            self.len() * n >= len,
+            "the length after repeating should not be less than {}",
+            len
+        );
+        match_trust_iter!(self; Dynamic(e) => {
+            let iter = e.flat_map(move |v| std::iter::repeat_n(v, n)).take(len);
+            Ok(iter.to_trust(len).into())
+        },)
+    }
+
+    /// count the valid values in each window
+    pub fn rolling_valid_count(self, window: usize) -> TResult<Vec<usize>> {
+        tensure!(window > 0, "window should be greater than 0");
+        let valid = self.valid_mask()?;
+        let mut n = 0;
+        Ok(valid
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                n += *v as usize;
+                if i >= window && valid[i - window] {
+                    n -= 1;
+                }
+                n
+            })
+            .collect())
+    }
+
+    /// rolling sum which keeps the dtype of the data, the result is the same as
+    /// evaluating `vsum` on each window: none values are ignored and the sum of
+    /// a window without valid values is zero
+    pub fn rolling_vsum<'b>(self, window: usize) -> TResult<DynVec<'b>> {
+        tensure!(window > 0, "window should be greater than 0");
+        match_trust_iter!(self; Numeric(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(ts_sum(&vec, window).into())
+        },)
+    }
+}
+
+/// calculate the rolling aggregation of a polars series
+#[cfg(feature = "pl")]
+pub fn series_rolling_agg(
+    s: &Series,
+    agg: RollingAgg,
+    window: usize,
+    min_periods: Option<usize>,
+    center: bool,
+) -> TResult<Vec<f64>> {
+    use tevec::polars::prelude::DataType::*;
+    tensure!(window > 0, "window should be greater than 0");
+    let mp = min_periods;
+    match s.dtype() {
+        Int32 => Ok(rolling_agg(s.i32().unwrap(), agg, window, mp, center)),
+        Int64 => Ok(rolling_agg(s.i64().unwrap(), agg, window, mp, center)),
+        Float32 => Ok(rolling_agg(s.f32().unwrap(), agg, window, mp, center)),
+        Float64 => Ok(rolling_agg(s.f64().unwrap(), agg, window, mp, center)),
+        dtype => tbail!("rolling is not supported for dtype {:?}", dtype),
+    }
+}
@@ -0,0 +1,466 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use std::collections::VecDeque;
+use std::ops::Sub;
+use tevec::dtype::chrono::{DateTime as CrDateTime, Utc};
+use tevec::ndarray::ArrayD;
+
+/// Which sides of the time window are closed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClosedWindow {
+    Left,
+    #[default]
+    Right,
+    Both,
+    None,
+}
+
+impl ClosedWindow {
+    pub fn parse(closed: &str) -> TResult<Self> {
+        match closed.to_lowercase().as_str() {
+            "left" => Ok(ClosedWindow::Left),
+            "right" => Ok(ClosedWindow::Right),
+            "both" => Ok(ClosedWindow::Both),
+            "none" => Ok(ClosedWindow::None),
+            _ => tbail!(
+                "closed should be one of left, right, both and none, got {}",
+                closed
+            ),
+        }
+    }
+
+    #[inline]
+    fn left_closed(&self) -> bool {
+        matches!(self, ClosedWindow::Left | ClosedWindow::Both)
+    }
+
+    #[inline]
+    fn right_closed(&self) -> bool {
+        matches!(self, ClosedWindow::Right | ClosedWindow::Both)
+    }
+}
+
+/// get the bounds `[start, end)` of the window of each row, the window of row `i`
+/// contains the rows whose time is in `(t_i - period, t_i]` if closed is right.
+///
+/// the times should be sorted in ascending order, so both start and end are monotonic
+fn time_window_bounds<U: TimeUnitTrait>(
+    times: &[DateTime<U>],
+    period: TimeDelta,
+    closed: ClosedWindow,
+) -> TResult<Vec<(usize, usize)>>
+where
+    DateTime<U>: Sub<TimeDelta, Output = DateTime<U>>,
+{
+    let mut bounds = Vec::with_capacity(times.len());
+    let (mut start, mut end) = (0, 0);
+    for (i, t) in times.iter().enumerate() {
+        tensure!(t.is_not_nat(), "time of rolling window should not be none");
+        let ti = t.into_i64();
+        if i > 0 {
+            tensure!(
+                ti >= times[i - 1].into_i64(),
+                "time of rolling window should be sorted in ascending order"
+            );
+        }
+        let lower = (*t - period).into_i64();
+        while start <= i
+            && (times[start].into_i64() < lower
+                || (!closed.left_closed() && times[start].into_i64() == lower))
+        {
+            start += 1;
+        }
+        if closed.right_closed() {
+            end = i + 1;
+        } else {
+            while end < i && times[end].into_i64() < ti {
+                end += 1;
+            }
+        }
+        bounds.push((start.min(end), end));
+    }
+    Ok(bounds)
+}
+
+/// Which time labels the windows of a dynamic group by
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WindowLabel {
+    /// the start of the window
+    #[default]
+    Left,
+    /// the end of the window
+    Right,
+    /// the time of the first row in the window
+    DataPoint,
+}
+
+/// get the bounds `[start, end)` of the rows in each regular window and the label
+/// of the window. The windows start at the first time truncated by `every` plus `offset`
+/// and move forward by `every`, each window covers `period`. Empty windows are skipped.
+///
+/// the times should be sorted in ascending order
+#[allow(clippy::type_complexity)]
+fn dynamic_window_bounds<U: TimeUnitTrait>(
+    times: &[DateTime<U>],
+    every: TimeDelta,
+    period: TimeDelta,
+    offset: Option<TimeDelta>,
+    closed: ClosedWindow,
+    label: WindowLabel,
+) -> TResult<(Vec<(usize, usize)>, Vec<DateTime<U>>)>
+where
+    DateTime<U>: From<CrDateTime<Utc>> + TryInto<CrDateTime<Utc>>,
+    <DateTime<U> as TryInto<CrDateTime<Utc>>>::Error: std::fmt::Debug,
+{
+    let values: Vec<i64> = times.iter().map(|t| t.into_i64()).collect();
+    for (i, t) in times.iter().enumerate() {
+        tensure!(
+            t.is_not_nat(),
+            "time of dynamic group by should not be none"
+        );
+        tensure!(
+            i == 0 || values[i] >= values[i - 1],
+            "time of dynamic group by should be sorted in ascending order"
+        );
+    }
+    let (Some(first), Some(last)) = (<[i64]>::first(&values), <[i64]>::last(&values)) else {
+        return Ok((vec![], vec![]));
+    };
+    let mut start = times[0].duration_trunc(every);
+    if let Some(offset) = offset {
+        start = start + offset;
+    }
+    tensure!(
+        (start + every).into_i64() > start.into_i64(),
+        "every of dynamic group by should be positive"
+    );
+    tensure!(
+        (start + period).into_i64() > start.into_i64(),
+        "period of dynamic group by should be positive"
+    );
+    while start.into_i64() > *first {
+        start = start - every;
+    }
+    let (mut bounds, mut labels) = (vec![], vec![]);
+    while start.into_i64() <= *last {
+        let stop = start + period;
+        let (lower, upper) = (start.into_i64(), stop.into_i64());
+        let lo = values.partition_point(|v| *v < lower || (!closed.left_closed() && *v == lower));
+        let hi = values.partition_point(|v| *v < upper || (closed.right_closed() && *v == upper));
+        if lo < hi {
+            bounds.push((lo, hi));
+            labels.push(match label {
+                WindowLabel::Left => start,
+                WindowLabel::Right => stop,
+                WindowLabel::DataPoint => times[lo],
+            });
+        }
+        start = start + every;
+    }
+    Ok((bounds, labels))
+}
+
+/// the state of a window which can be updated incrementally. The moments are
+/// the central moments of the finite values updated in the Welford style, so
+/// that they don't lose precision when the values are far from zero, and the
+/// infinite values are only counted so that they leave no trace after they are
+/// removed from the window.
+#[derive(Default)]
+struct WindowState {
+    n: usize,
+    pos_inf: usize,
+    neg_inf: usize,
+    // count, compensated sum and central moments of the finite values
+    n_finite: usize,
+    sum: f64,
+    comp: f64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    // index candidates of the minimum and maximum value
+    min_idx: VecDeque<usize>,
+    max_idx: VecDeque<usize>,
+}
+
+impl WindowState {
+    /// add `v` to the neumaier compensated sum
+    #[inline]
+    fn add_sum(&mut self, v: f64) {
+        let t = self.sum + v;
+        if self.sum.abs() >= v.abs() {
+            self.comp += (self.sum - t) + v;
+        } else {
+            self.comp += (v - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    fn push(&mut self, agg: RollingAgg, values: &[f64], i: usize) {
+        let v = values[i];
+        if v.is_nan() {
+            return;
+        }
+        self.n += 1;
+        match agg {
+            RollingAgg::Min => {
+                while matches!(self.min_idx.back(), Some(j) if values[*j] > v) {
+                    self.min_idx.pop_back();
+                }
+                self.min_idx.push_back(i);
+            }
+            RollingAgg::Max => {
+                while matches!(self.max_idx.back(), Some(j) if values[*j] < v) {
+                    self.max_idx.pop_back();
+                }
+                self.max_idx.push_back(i);
+            }
+            RollingAgg::Count | RollingAgg::Median => {}
+            _ if v == f64::INFINITY => self.pos_inf += 1,
+            _ if v == f64::NEG_INFINITY => self.neg_inf += 1,
+            _ => {
+                self.add_sum(v);
+                self.n_finite += 1;
+                let n = self.n_finite as f64;
+                let delta = v - self.mean;
+                let delta_n = delta / n;
+                let delta_n2 = delta_n * delta_n;
+                let term = delta * delta_n * (n - 1.);
+                self.m4 += term * delta_n2 * (n * n - 3. * n + 3.) + 6. * delta_n2 * self.m2
+                    - 4. * delta_n * self.m3;
+                self.m3 += term * delta_n * (n - 2.) - 3. * delta_n * self.m2;
+                self.m2 += term;
+                self.mean += delta_n;
+            }
+        }
+    }
+
+    fn pop(&mut self, agg: RollingAgg, values: &[f64], i: usize) {
+        let v = values[i];
+        if v.is_nan() {
+            return;
+        }
+        self.n -= 1;
+        match agg {
+            RollingAgg::Min => {
+                if self.min_idx.front() == Some(&i) {
+                    self.min_idx.pop_front();
+                }
+            }
+            RollingAgg::Max => {
+                if self.max_idx.front() == Some(&i) {
+                    self.max_idx.pop_front();
+                }
+            }
+            RollingAgg::Count | RollingAgg::Median => {}
+            _ if v == f64::INFINITY => self.pos_inf -= 1,
+            _ if v == f64::NEG_INFINITY => self.neg_inf -= 1,
+            _ => {
+                self.add_sum(-v);
+                self.n_finite -= 1;
+                if self.n_finite == 0 {
+                    (self.sum, self.comp) = (0., 0.);
+                    (self.mean, self.m2, self.m3, self.m4) = (0., 0., 0., 0.);
+                    return;
+                }
+                // the inverse of the update in `push`, the moments of the rest
+                // values are used in the same place as in `push`
+                let n = (self.n_finite + 1) as f64;
+                let mean = self.mean - (v - self.mean) / (n - 1.);
+                let delta = v - mean;
+                let delta_n = delta / n;
+                let delta_n2 = delta_n * delta_n;
+                let term = delta * delta_n * (n - 1.);
+                self.m2 -= term;
+                self.m3 -= term * delta_n * (n - 2.) - 3. * delta_n * self.m2;
+                self.m4 -= term * delta_n2 * (n * n - 3. * n + 3.) + 6. * delta_n2 * self.m2
+                    - 4. * delta_n * self.m3;
+                self.mean = mean;
+            }
+        }
+    }
+
+    /// the formulas are the same as the fixed window kernels in tevec
+    fn value(&self, agg: RollingAgg, values: &[f64], min_periods: usize) -> f64 {
+        let min_periods = match agg {
+            RollingAgg::Std | RollingAgg::Var => min_periods.max(2),
+            RollingAgg::Skew => min_periods.max(3),
+            RollingAgg::Kurt => min_periods.max(4),
+            _ => min_periods.max(1),
+        };
+        if self.n < min_periods {
+            return f64::NAN;
+        }
+        let n = self.n;
+        let n_f64 = n as f64;
+        let inf = match (self.pos_inf > 0, self.neg_inf > 0) {
+            (false, false) => None,
+            (true, false) => Some(f64::INFINITY),
+            (false, true) => Some(f64::NEG_INFINITY),
+            (true, true) => Some(f64::NAN),
+        };
+        match agg {
+            RollingAgg::Count => n_f64,
+            RollingAgg::Min => values[self.min_idx[0]],
+            RollingAgg::Max => values[self.max_idx[0]],
+            RollingAgg::Median => unreachable!("median is calculated by the order statistic"),
+            RollingAgg::Sum => inf.unwrap_or(self.sum + self.comp),
+            RollingAgg::Mean => inf.unwrap_or((self.sum + self.comp) / n_f64),
+            _ if inf.is_some() => f64::NAN,
+            RollingAgg::Var | RollingAgg::Std => {
+                let var = self.m2 / n_f64;
+                let var = if var > EPS {
+                    var * n_f64 / (n - 1) as f64
+                } else {
+                    0.
+                };
+                if agg == RollingAgg::Std {
+                    var.sqrt()
+                } else {
+                    var
+                }
+            }
+            RollingAgg::Skew => {
+                let var = self.m2 / n_f64;
+                if var <= EPS {
+                    return 0.;
+                }
+                let adjust = ((n * (n - 1)) as f64).sqrt() / (n - 2) as f64;
+                adjust * (self.m3 / n_f64) / var.powf(1.5)
+            }
+            RollingAgg::Kurt => {
+                let var = self.m2 / n_f64;
+                if var <= EPS {
+                    return 0.;
+                }
+                let out = self.m4 / n_f64 / (var * var);
+                1. / ((n - 2) * (n - 3)) as f64
+                    * ((n.pow(2) - 1) as f64 * out - (3 * (n - 1).pow(2)) as f64)
+            }
+        }
+    }
+}
+
+/// calculate the aggregation of each window `[start, end)`, the bounds should be
+/// monotonic so that the window can be updated incrementally.
+/// None values are ignored.
+pub fn rolling_agg_by<V: Vec1View<T>, T: IsNone>(
+    v: &V,
+    agg: RollingAgg,
+    bounds: &[(usize, usize)],
+    min_periods: Option<usize>,
+) -> Vec<f64>
+where
+    T::Inner: Number,
+{
+    let values: Vec<f64> = v
+        .titer()
+        .map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64()))
+        .collect();
+    if agg == RollingAgg::Median {
+        let agg = RollingOrderAgg::Quantile {
+            q: 0.5,
+            method: QuantileMethod::Linear,
+        };
+        return rolling_order_agg(&values, agg, bounds, min_periods);
+    }
+    let min_periods = min_periods.unwrap_or(1);
+    let mut state = WindowState::default();
+    let (mut start, mut end) = (0, 0);
+    bounds
+        .iter()
+        .map(|&(s, e)| {
+            while end < e {
+                state.push(agg, &values, end);
+                end += 1;
+            }
+            while start < s {
+                state.pop(agg, &values, start);
+                start += 1;
+            }
+            state.value(agg, &values, min_periods)
+        })
+        .collect()
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// get the bounds `[start, end)` of the time window of each row,
+    /// see [`ClosedWindow`] for the sides of the window
+    pub fn time_window_bounds(
+        self,
+        period: TimeDelta,
+        closed: ClosedWindow,
+    ) -> TResult<Vec<(usize, usize)>> {
+        match_trust_iter!(self; Time(e) => {
+            let times = e.collect_trusted_to_vec();
+            time_window_bounds(&times, period, closed)
+        },)
+    }
+
+    /// get the bounds `[start, end)` of the rows in each regular window and the
+    /// labels of the windows, see [`WindowLabel`]
+    pub fn dynamic_window_bounds<'b>(
+        self,
+        every: TimeDelta,
+        period: TimeDelta,
+        offset: Option<TimeDelta>,
+        closed: ClosedWindow,
+        label: WindowLabel,
+    ) -> TResult<(Vec<(usize, usize)>, DynVec<'b>)> {
+        match_trust_iter!(self; Time(e) => {
+            let times = e.collect_trusted_to_vec();
+            let (bounds, labels) =
+                dynamic_window_bounds(&times, every, period, offset, closed, label)?;
+            Ok((bounds, labels.into()))
+        },)
+    }
+
+    /// aggregate the values in each window `[start, end)`
+    pub fn rolling_agg_by(
+        self,
+        agg: RollingAgg,
+        bounds: &[(usize, usize)],
+        min_periods: Option<usize>,
+    ) -> TResult<Vec<f64>> {
+        tensure!(self.len() == bounds.len(), lm, self.len(), bounds.len());
+        match_trust_iter!(self; Numeric(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(rolling_agg_by(&vec, agg, bounds, min_periods))
+        },)
+    }
+
+    /// count the valid values in each window `[start, end)`
+    pub fn window_valid_count(self, bounds: &[(usize, usize)]) -> TResult<Vec<usize>> {
+        tensure!(self.len() == bounds.len(), lm, self.len(), bounds.len());
+        Ok(bounds_valid_count(&self.valid_mask()?, bounds))
+    }
+}
+
+impl<'a> DynArray<'a> {
+    /// aggregate the values in each window `[start, end)` of each lane along `axis`
+    pub fn rolling_agg_by(
+        &self,
+        agg: RollingAgg,
+        bounds: &[(usize, usize)],
+        min_periods: Option<usize>,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let axis = axis.unwrap_or(0);
+        tensure!(
+            axis < self.ndim(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            self.ndim()
+        );
+        let len = self.shape()[axis];
+        tensure!(len == bounds.len(), lm, len, bounds.len());
+        match_array!(self; Numeric(a) => {
+            Ok(a.view().calc_map_trust_iter_func(
+                |lane| Box::new(rolling_agg_by(&lane, agg, bounds, min_periods).into_iter()),
+                Some(axis),
+                par,
+            ))
+        },)
+    }
+}
@@ -0,0 +1,201 @@
+use crate::prelude::*;
+use tevec::ndarray::ArrayD;
+
+/// Aggregations on the order statistics of a rolling window
+#[derive(Clone, Copy)]
+pub enum RollingOrderAgg {
+    /// rank of the value of the current row in its window, equal values
+    /// get the average rank, divided by the valid count if `pct` is true
+    Rank { pct: bool },
+    /// quantile of the window, `q` should be between 0 and 1
+    Quantile { q: f64, method: QuantileMethod },
+}
+
+impl RollingOrderAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            RollingOrderAgg::Rank { .. } => "rolling_rank",
+            RollingOrderAgg::Quantile { .. } => "rolling_quantile",
+        }
+    }
+
+    #[inline]
+    pub fn check(&self) -> TResult<()> {
+        if let RollingOrderAgg::Quantile { q, .. } = self {
+            tensure!(
+                (0. ..=1.).contains(q),
+                "q must be between 0 and 1, find {}",
+                q
+            );
+        }
+        Ok(())
+    }
+}
+
+/// count of the values in a window indexed by their position in the sorted
+/// distinct values (a fenwick tree), so that inserting, removing, ranking and
+/// selecting the k-th smallest value all cost O(log n)
+struct OrderStats {
+    tree: Vec<usize>,
+}
+
+impl OrderStats {
+    #[inline]
+    fn new(n: usize) -> Self {
+        OrderStats {
+            tree: vec![0; n + 1],
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, pos: usize) {
+        let mut i = pos + 1;
+        while i < self.tree.len() {
+            self.tree[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    #[inline]
+    fn remove(&mut self, pos: usize) {
+        let mut i = pos + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// count of the values whose position is less than `pos`
+    #[inline]
+    fn count_less(&self, pos: usize) -> usize {
+        let (mut i, mut count) = (pos, 0);
+        while i > 0 {
+            count += self.tree[i];
+            i -= i & i.wrapping_neg();
+        }
+        count
+    }
+
+    /// position of the k-th (0-based) smallest value, k should be less than
+    /// the count of the values
+    #[inline]
+    fn kth(&self, mut k: usize) -> usize {
+        let n = self.tree.len() - 1;
+        let mut pos = 0;
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= k {
+                pos += step;
+                k -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// calculate the order statistic of each window `[start, end)`, the bounds
+/// should be monotonic so that the window can be updated incrementally.
+/// NaN values are ignored.
+pub fn rolling_order_agg(
+    values: &[f64],
+    agg: RollingOrderAgg,
+    bounds: &[(usize, usize)],
+    min_periods: Option<usize>,
+) -> Vec<f64> {
+    let mut distinct: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+    <[f64]>::sort_unstable_by(&mut distinct, |a, b| a.total_cmp(b));
+    distinct.dedup();
+    let pos: Vec<Option<usize>> = values
+        .iter()
+        .map(|v| (!v.is_nan()).then(|| distinct.partition_point(|a| a < v)))
+        .collect();
+    let min_periods = min_periods.unwrap_or(1).max(1);
+    let mut stats = OrderStats::new(distinct.len());
+    let (mut start, mut end, mut n) = (0, 0, 0);
+    bounds
+        .iter()
+        .enumerate()
+        .map(|(i, &(s, e))| {
+            while end < e {
+                if let Some(p) = pos[end] {
+                    stats.insert(p);
+                    n += 1;
+                }
+                end += 1;
+            }
+            while start < s {
+                if let Some(p) = pos[start] {
+                    stats.remove(p);
+                    n -= 1;
+                }
+                start += 1;
+            }
+            if n < min_periods {
+                return f64::NAN;
+            }
+            match agg {
+                RollingOrderAgg::Rank { pct } => {
+                    let Some(p) = pos[i] else {
+                        return f64::NAN;
+                    };
+                    // the current value is counted even if it is out of the window
+                    let outside = !(s..e).contains(&i) as usize;
+                    let less = stats.count_less(p);
+                    let equal = stats.count_less(p + 1) - less + outside;
+                    let rank = less as f64 + (equal + 1) as f64 / 2.;
+                    if pct {
+                        rank / (n + outside) as f64
+                    } else {
+                        rank
+                    }
+                }
+                RollingOrderAgg::Quantile { q, method } => {
+                    let idx = (n - 1) as f64 * q;
+                    let (lo, hi) = (idx.floor() as usize, idx.ceil() as usize);
+                    let vi = distinct[stats.kth(lo)];
+                    let vj = distinct[stats.kth(hi)];
+                    match method {
+                        QuantileMethod::Linear => vi + (vj - vi) * (idx - lo as f64),
+                        QuantileMethod::Lower => vi,
+                        QuantileMethod::Higher => vj,
+                        QuantileMethod::MidPoint => (vi + vj) / 2.,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+impl<'a> DynArray<'a> {
+    /// calculate the order statistic in each window `[start, end)` of each lane
+    /// along `axis`
+    pub fn rolling_order_agg(
+        &self,
+        agg: RollingOrderAgg,
+        bounds: &[(usize, usize)],
+        min_periods: Option<usize>,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        agg.check()?;
+        let axis = axis.unwrap_or(0);
+        let shape = self.shape();
+        tensure!(
+            axis < shape.len(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            shape.len()
+        );
+        tensure!(shape[axis] == bounds.len(), lm, shape[axis], bounds.len());
+        Ok(self.to_f64_array()?.calc_map_trust_iter_func(
+            |lane| {
+                let lane = lane.iter().copied().collect::<Vec<_>>();
+                Box::new(rolling_order_agg(&lane, agg, bounds, min_periods).into_iter())
+            },
+            Some(axis),
+            par,
+        ))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use tevec::ndarray::ArrayD;
+
+/// Weighted aggregations on a fixed rolling window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightedAgg {
+    Sum,
+    Mean,
+}
+
+impl WeightedAgg {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeightedAgg::Sum => "rolling_weighted_sum",
+            WeightedAgg::Mean => "rolling_weighted_mean",
+        }
+    }
+}
+
+/// Generated weights of a window, the weights are ordered from the oldest
+/// row to the latest row of the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightKernel {
+    /// `1, 2, ..., window`, the latest row has the largest weight
+    Linear,
+    /// the weight of the row `k` rows before the latest is `(1 - alpha)^k`,
+    /// the same decay as the exponentially weighted window
+    Exp(EwmDecay),
+    /// triangular weights peaking at the middle of the window, the same as
+    /// the `triang` window of scipy
+    Triangular,
+}
+
+impl WeightKernel {
+    pub fn weights(&self, window: usize) -> TResult<Vec<f64>> {
+        tensure!(window > 0, "window should be greater than 0");
+        match self {
+            WeightKernel::Linear => Ok((1..=window).map(|i| i as f64).collect()),
+            WeightKernel::Exp(decay) => {
+                let beta = 1. - decay.alpha()?;
+                Ok((0..window)
+                    .map(|i| beta.powi((window - 1 - i) as i32))
+                    .collect())
+            }
+            WeightKernel::Triangular => {
+                let m = window as f64;
+                Ok((1..=window)
+                    .map(|i| {
+                        // position counted from the nearer edge of the window
+                        let i = i.min(window + 1 - i) as f64;
+                        if window % 2 == 1 {
+                            2. * i / (m + 1.)
+                        } else {
+                            (2. * i - 1.) / m
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// calculate the weighted aggregation of each fixed window, the weights are
+/// aligned with the end of the window, so a shrunk window at the start uses
+/// the latest weights.
+///
+/// NaN values are ignored, the mean is renormalized by the weights of the
+/// valid values in the window.
+pub fn rolling_weighted_agg(
+    values: &[f64],
+    weights: &[f64],
+    agg: WeightedAgg,
+    min_periods: Option<usize>,
+    center: bool,
+) -> Vec<f64> {
+    let window = weights.len();
+    let offset = center_offset(window, center);
+    let min_periods = min_periods.unwrap_or(1).max(1);
+    (0..values.len())
+        .map(|i| {
+            // the rows of the window are `[end - window, end)`
+            let end = i + offset + 1;
+            let (mut n, mut sum, mut w_sum) = (0, 0., 0.);
+            for (j, w) in weights.iter().enumerate() {
+                let Some(row) = (end + j).checked_sub(window) else {
+                    continue;
+                };
+                if let Some(v) = <[f64]>::get(values, row).filter(|v| !v.is_nan()) {
+                    n += 1;
+                    sum += w * v;
+                    w_sum += w;
+                }
+            }
+            if n < min_periods {
+                f64::NAN
+            } else {
+                match agg {
+                    WeightedAgg::Sum => sum,
+                    WeightedAgg::Mean => sum / w_sum,
+                }
+            }
+        })
+        .collect()
+}
+
+impl<'a> DynArray<'a> {
+    /// weighted aggregation on each fixed window of each lane along `axis`
+    pub fn rolling_weighted_agg(
+        &self,
+        weights: &[f64],
+        agg: WeightedAgg,
+        min_periods: Option<usize>,
+        center: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<ArrayD<f64>> {
+        let axis = axis.unwrap_or(0);
+        tensure!(
+            axis < self.ndim(),
+            "axis {} is out of bounds for array of dimension {}",
+            axis,
+            self.ndim()
+        );
+        Ok(self.to_f64_array()?.calc_map_trust_iter_func(
+            |lane| {
+                let lane = lane.iter().copied().collect::<Vec<_>>();
+                let out = rolling_weighted_agg(&lane, weights, agg, min_periods, center);
+                Box::new(out.into_iter())
+            },
+            Some(axis),
+            par,
+        ))
+    }
+}
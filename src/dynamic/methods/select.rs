@@ -0,0 +1,169 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use std::borrow::Cow;
+use tevec::ndarray::{Axis, Slice};
+
+/// get the start and end index of a slice, negative offset counts from the end
+#[inline]
+pub fn slice_bound(offset: i64, len: usize, total: usize) -> (usize, usize) {
+    let start = if offset < 0 {
+        (total as i64 + offset).max(0) as usize
+    } else {
+        (offset as usize).min(total)
+    };
+    (start, (start + len).min(total))
+}
+
+/// resolve the index, negative index counts from the end
+#[inline]
+pub fn resolve_index(idx: i64, len: usize) -> TResult<usize> {
+    let i = if idx < 0 { idx + len as i64 } else { idx };
+    if i < 0 || i as usize >= len {
+        tbail!("index {} out of bounds for length {}", idx, len)
+    }
+    Ok(i as usize)
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// lazily slice the iterator
+    #[inline]
+    pub fn slice(self, start: usize, end: usize) -> TResult<Self> {
+        tensure!(
+            start <= end && end <= self.len(),
+            "slice {}..{} out of bounds",
+            start,
+            end
+        );
+        match_trust_iter!(self; Dynamic(e) => {
+            Ok(e.skip(start).take(end - start).to_trust(end - start).into())
+        },)
+    }
+
+    /// lazily keep the elements where the mask is true
+    #[inline]
+    pub fn filter(self, mask: Vec<bool>) -> TResult<Self> {
+        tensure!(mask.len() == self.len(), lm, mask.len(), self.len());
+        let n = mask.iter().filter(|m| **m).count();
+        match_trust_iter!(self; Dynamic(e) => {
+            Ok(e.zip(mask).filter_map(|(v, m)| m.then_some(v)).to_trust(n).into())
+        },)
+    }
+
+    /// collect the iterator as a boolean mask, none values are treated as false
+    #[inline]
+    pub fn into_mask(self) -> TResult<Vec<bool>> {
+        match_trust_iter!(self;
+            Bool(e) => Ok(e.collect_trusted_to_vec()),
+            OptBool(e) => Ok(e.map(|v| v.unwrap_or(false)).collect()),
+        )
+    }
+
+    /// collect the iterator as indices of data with length `len`,
+    /// negative indices count from the end
+    #[inline]
+    pub fn into_index(self, len: usize) -> TResult<Vec<usize>> {
+        match_trust_iter!(self;
+            PureInt(e) => e.map(|i| resolve_index(i.cast(), len)).collect(),
+            OptInt(e) => e.map(|i| match i {
+                Some(i) => resolve_index(i.cast(), len),
+                None => tbail!("index should not be none"),
+            }).collect(),
+            VecUsize(e) => e.flatten().map(|i| resolve_index(i as i64, len)).collect(),
+        )
+    }
+}
+
+impl<'a> DynVec<'a> {
+    /// slice the vector without copying the data
+    #[inline]
+    pub fn slice(&self, start: usize, end: usize) -> TResult<DynVec<'_>> {
+        tensure!(
+            start <= end && end <= self.len(),
+            "slice {}..{} out of bounds",
+            start,
+            end
+        );
+        match_vec!(self; Dynamic(v) => Ok((&v[start..end]).into()),)
+    }
+
+    /// consume the vector and slice it, borrowed data is sliced without copying
+    #[inline]
+    pub fn into_slice(self, start: usize, end: usize) -> TResult<DynVec<'a>> {
+        tensure!(
+            start <= end && end <= self.len(),
+            "slice {}..{} out of bounds",
+            start,
+            end
+        );
+        match_vec!(self; Dynamic(v) => {
+            match v {
+                Cow::Borrowed(v) => Ok((&v[start..end]).into()),
+                Cow::Owned(mut v) => {
+                    v.truncate(end);
+                    v.drain(..start);
+                    Ok(v.into())
+                },
+            }
+        },)
+    }
+}
+
+impl<'a, T: Clone> ArbArray<'a, T> {
+    /// slice the array along the axis, this will not copy the data
+    #[inline]
+    pub fn slice_axis(self, axis: usize, start: usize, end: usize) -> TResult<Self> {
+        tensure!(axis < self.ndim(), "axis {} out of bounds", axis);
+        let len = self.view().len_of(Axis(axis));
+        tensure!(
+            start <= end && end <= len,
+            "slice {}..{} out of bounds",
+            start,
+            end
+        );
+        match_arb!(self; Owned(a) | View(a) | ViewMut(a) => {
+            let mut a = a;
+            a.slice_axis_inplace(Axis(axis), Slice::from(start..end));
+            Ok(a.into())
+        },)
+    }
+
+    /// select the elements at the given indices along the axis
+    #[inline]
+    pub fn select_axis<'b>(&self, axis: usize, idx: &[usize]) -> TResult<ArbArray<'b, T>> {
+        tensure!(axis < self.ndim(), "axis {} out of bounds", axis);
+        let view = self.view();
+        let len = view.len_of(Axis(axis));
+        if let Some(i) = idx.iter().find(|i| **i >= len) {
+            tbail!(oob(*i, len))
+        }
+        Ok(view.select(Axis(axis), idx).into())
+    }
+
+    /// reverse the array along the axis, this will not copy the data
+    #[inline]
+    pub fn invert_axis(self, axis: usize) -> TResult<Self> {
+        tensure!(axis < self.ndim(), "axis {} out of bounds", axis);
+        match_arb!(self; Owned(a) | View(a) | ViewMut(a) => {
+            let mut a = a;
+            a.invert_axis(Axis(axis));
+            Ok(a.into())
+        },)
+    }
+}
+
+impl<'a> DynArray<'a> {
+    #[inline]
+    pub fn slice_axis(self, axis: usize, start: usize, end: usize) -> TResult<Self> {
+        match_array!(self; Dynamic(a) => Ok(a.slice_axis(axis, start, end)?.into()),)
+    }
+
+    #[inline]
+    pub fn select_axis<'b>(&self, axis: usize, idx: &[usize]) -> TResult<DynArray<'b>> {
+        match_array!(self; Dynamic(a) => Ok(a.select_axis(axis, idx)?.into()),)
+    }
+
+    #[inline]
+    pub fn invert_axis(self, axis: usize) -> TResult<Self> {
+        match_array!(self; Dynamic(a) => Ok(a.invert_axis(axis)?.into()),)
+    }
+}
@@ -0,0 +1,148 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use std::cmp::Ordering;
+
+/// compare two values, none values are placed according to `nulls_last`
+/// no matter the sort is descending or not
+#[inline]
+pub fn null_sort_cmp<T: IsNone>(a: &T, b: &T, descending: bool, nulls_last: bool) -> Ordering
+where
+    T::Inner: PartialOrd,
+{
+    match (a.as_opt(), b.as_opt()) {
+        (Some(va), Some(vb)) => {
+            let ord = va.partial_cmp(vb).unwrap_or(Ordering::Equal);
+            if descending {
+                ord.reverse()
+            } else {
+                ord
+            }
+        }
+        (None, None) => Ordering::Equal,
+        (None, _) if nulls_last => Ordering::Greater,
+        (None, _) => Ordering::Less,
+        (_, None) if nulls_last => Ordering::Less,
+        (_, None) => Ordering::Greater,
+    }
+}
+
+/// return the indices that would sort the values, the sort is stable
+pub fn arg_sort_by<I, T>(iter: I, descending: bool, nulls_last: bool) -> Vec<usize>
+where
+    I: IntoIterator<Item = T>,
+    T: IsNone,
+    T::Inner: PartialOrd,
+{
+    let values: Vec<T> = iter.into_iter().collect();
+    let mut idx: Vec<usize> = (0..values.len()).collect();
+    idx.sort_by(|a, b| null_sort_cmp(&values[*a], &values[*b], descending, nulls_last));
+    idx
+}
+
+/// return the indices of the `k` largest (or smallest if `descending` is false)
+/// values in O(n), the result is sorted by value and none values are never selected
+pub fn arg_top_k_by<T>(values: &[T], k: usize, descending: bool) -> Vec<usize>
+where
+    T: IsNone,
+    T::Inner: PartialOrd,
+{
+    let mut idx: Vec<usize> = (0..values.len())
+        .filter(|i| values[*i].not_none())
+        .collect();
+    let k = k.min(idx.len());
+    if k == 0 {
+        return vec![];
+    }
+    // compare index as well so that equal values are selected in order of appearance
+    let cmp = |a: &usize, b: &usize| {
+        null_sort_cmp(&values[*a], &values[*b], descending, true).then(a.cmp(b))
+    };
+    if k < idx.len() {
+        idx.select_nth_unstable_by(k - 1, cmp);
+        idx.truncate(k);
+    }
+    idx.sort_by(cmp);
+    idx
+}
+
+impl<'a> DynTrustIter<'a> {
+    #[inline]
+    pub fn sort(self, descending: bool, nulls_last: bool) -> TResult<Self> {
+        match_trust_iter!(self; (Normal | String | TimeRelated)(e) => {
+            let mut vec = e.collect_trusted_to_vec();
+            vec.sort_by(|a, b| null_sort_cmp(a, b, descending, nulls_last));
+            Ok(vec.into_iter().into())
+        },)
+    }
+
+    #[inline]
+    pub fn arg_sort(self, descending: bool, nulls_last: bool) -> TResult<Vec<usize>> {
+        match_trust_iter!(self; (Normal | String | TimeRelated)(e) => {
+            Ok(arg_sort_by(e, descending, nulls_last))
+        },)
+    }
+
+    #[inline]
+    pub fn reverse(self) -> TResult<Self> {
+        match_trust_iter!(self; Dynamic(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(vec.into_iter().rev().into())
+        },)
+    }
+
+    /// take the elements at the given indices
+    #[inline]
+    #[allow(clippy::clone_on_copy)]
+    pub fn take(self, idx: Vec<usize>) -> TResult<Self> {
+        let len = self.len();
+        if let Some(i) = idx.iter().find(|i| **i >= len) {
+            tbail!(oob(*i, len))
+        }
+        match_trust_iter!(self; Dynamic(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(idx.into_iter().map(move |i| vec[i].clone()).into())
+        },)
+    }
+
+    /// get the indices of the `k` largest values, or the `k` smallest values
+    /// if `descending` is false
+    #[inline]
+    pub fn arg_top_k(self, k: usize, descending: bool) -> TResult<Vec<usize>> {
+        match_trust_iter!(self; (Normal | String | TimeRelated)(e) => {
+            Ok(arg_top_k_by(&e.collect_trusted_to_vec(), k, descending))
+        },)
+    }
+
+    /// get the `k` largest values, or the `k` smallest values if `descending` is false
+    #[inline]
+    #[allow(clippy::clone_on_copy)]
+    pub fn top_k(self, k: usize, descending: bool) -> TResult<Self> {
+        match_trust_iter!(self; (Normal | String | TimeRelated)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let idx = arg_top_k_by(&vec, k, descending);
+            Ok(idx.into_iter().map(move |i| vec[i].clone()).into())
+        },)
+    }
+}
+
+impl<'a> DynArray<'a> {
+    /// get the indices of the top k values of each lane along the axis
+    #[inline]
+    pub fn arg_top_k<'b>(
+        &self,
+        k: usize,
+        descending: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> TResult<DynArray<'b>> {
+        match_array!(self; (Normal | String | TimeRelated)(a) => {
+            let view = a.view();
+            let out = view.calc_agg_func(
+                |lane| arg_top_k_by(&lane.to_vec(), k, descending),
+                axis,
+                par,
+            );
+            Ok(out.into())
+        },)
+    }
+}
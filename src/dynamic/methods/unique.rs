@@ -0,0 +1,224 @@
+#![allow(unreachable_patterns)]
+use crate::prelude::*;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A type that can be used as a key of hash map.
+///
+/// Float values are hashed by their bits, all NaNs are treated as equal
+/// and `-0.0` is treated as equal to `0.0`.
+pub trait HashKey {
+    type Key<'a>: Hash + Eq
+    where
+        Self: 'a;
+
+    fn hash_key(&self) -> Self::Key<'_>;
+}
+
+macro_rules! impl_hash_key {
+    ($($(#[$meta: meta])? $ty: ty),*) => {
+        $(
+            $(#[$meta])?
+            impl HashKey for $ty {
+                type Key<'a> = $ty;
+
+                #[inline]
+                #[allow(clippy::clone_on_copy)]
+                fn hash_key(&self) -> $ty {
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+impl_hash_key!(
+    bool,
+    u8,
+    i32,
+    i64,
+    u64,
+    usize,
+    #[cfg(feature = "time")]
+    TimeDelta
+);
+
+macro_rules! impl_float_hash_key {
+    ($($ty: ty),*) => {
+        $(
+            impl HashKey for $ty {
+                type Key<'a> = u64;
+
+                #[inline]
+                fn hash_key(&self) -> u64 {
+                    if self.is_nan() {
+                        <$ty>::NAN.to_bits() as u64
+                    } else if *self == 0. {
+                        0
+                    } else {
+                        self.to_bits() as u64
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_float_hash_key!(f32, f64);
+
+impl HashKey for String {
+    type Key<'a> = &'a str;
+
+    #[inline]
+    fn hash_key(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(feature = "time")]
+impl<U: TimeUnitTrait> HashKey for DateTime<U> {
+    type Key<'a>
+        = i64
+    where
+        U: 'a;
+
+    #[inline]
+    fn hash_key(&self) -> i64 {
+        self.into_i64()
+    }
+}
+
+impl<T: HashKey> HashKey for Option<T> {
+    type Key<'a>
+        = Option<T::Key<'a>>
+    where
+        T: 'a;
+
+    #[inline]
+    fn hash_key(&self) -> Self::Key<'_> {
+        self.as_ref().map(HashKey::hash_key)
+    }
+}
+
+/// assign a group id to each value, the ids are given in order of first appearance.
+///
+/// return the group id of each value and the index of the first value of each group
+pub fn hash_group_ids<T: HashKey>(values: &[T]) -> (Vec<usize>, Vec<usize>) {
+    let mut map = HashMap::with_capacity(values.len());
+    let mut first = Vec::new();
+    let ids = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            *map.entry(v.hash_key()).or_insert_with(|| {
+                first.push(i);
+                first.len() - 1
+            })
+        })
+        .collect();
+    (ids, first)
+}
+
+/// count the number of values in each group
+#[inline]
+pub fn group_counts(ids: &[usize], n_groups: usize) -> Vec<usize> {
+    let mut counts = vec![0; n_groups];
+    ids.iter().for_each(|id| counts[*id] += 1);
+    counts
+}
+
+/// keep the first value of each group
+#[inline]
+fn keep_first<T>(values: Vec<T>, first: &[usize]) -> Vec<T> {
+    let mut is_first = vec![false; values.len()];
+    first.iter().for_each(|i| is_first[*i] = true);
+    values
+        .into_iter()
+        .zip(is_first)
+        .filter_map(|(v, f)| f.then_some(v))
+        .collect()
+}
+
+impl<'a> DynTrustIter<'a> {
+    /// get the unique values, if `maintain_order` is false the values are sorted
+    pub fn unique(self, maintain_order: bool) -> TResult<Self> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let (_, first) = hash_group_ids(&vec);
+            let mut out = keep_first(vec, &first);
+            if !maintain_order {
+                out.sort_by(|a, b| null_sort_cmp(a, b, false, true));
+            }
+            Ok(out.into_iter().into())
+        },)
+    }
+
+    /// assign a group id to each value, see [`hash_group_ids`]
+    #[inline]
+    pub fn group_ids(self) -> TResult<(Vec<usize>, Vec<usize>)> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(hash_group_ids(&vec))
+        },)
+    }
+
+    #[inline]
+    pub fn n_unique(self) -> TResult<usize> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            Ok(hash_group_ids(&vec).1.len())
+        },)
+    }
+
+    /// get the unique values in order of first appearance and the count of each value,
+    /// if `sort` is true the result is sorted by count in descending order
+    pub fn value_counts(self, sort: bool) -> TResult<(Self, Vec<usize>)> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let (ids, first) = hash_group_ids(&vec);
+            let counts = group_counts(&ids, first.len());
+            let values = keep_first(vec, &first);
+            if sort {
+                let mut pairs: Vec<_> = values.into_iter().zip(counts).collect();
+                pairs.sort_by_key(|p| std::cmp::Reverse(p.1));
+                let (values, counts): (Vec<_>, Vec<_>) = pairs.into_iter().unzip();
+                Ok((values.into_iter().into(), counts))
+            } else {
+                Ok((values.into_iter().into(), counts))
+            }
+        },)
+    }
+
+    /// whether the value appears more than once
+    pub fn is_duplicated(self) -> TResult<Self> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let (ids, first) = hash_group_ids(&vec);
+            let counts = group_counts(&ids, first.len());
+            Ok(ids.into_iter().map(move |id| counts[id] > 1).into())
+        },)
+    }
+
+    /// whether the value appears only once
+    pub fn is_unique(self) -> TResult<Self> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let (ids, first) = hash_group_ids(&vec);
+            let counts = group_counts(&ids, first.len());
+            Ok(ids.into_iter().map(move |id| counts[id] == 1).into())
+        },)
+    }
+
+    /// whether the value is the first occurrence
+    pub fn is_first(self) -> TResult<Self> {
+        match_trust_iter!(self; (TpHash | PlOpt | OptUsize)(e) => {
+            let vec = e.collect_trusted_to_vec();
+            let (ids, first) = hash_group_ids(&vec);
+            Ok(ids
+                .into_iter()
+                .enumerate()
+                .map(move |(i, id)| first[id] == i)
+                .into())
+        },)
+    }
+}
@@ -0,0 +1,93 @@
+use crate::prelude::*;
+
+/// How to compute the bounds of winsorization, unlike `WinsorizeMethod` of
+/// tevec the quantile bounds can be asymmetric
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WinsorizeBounds {
+    /// the `lo` and `hi` quantiles of the values
+    Quantile { lo: f64, hi: f64 },
+    /// `n` sample standard deviations around the mean
+    Std(f64),
+    /// `n` median absolute deviations around the median
+    Mad(f64),
+}
+
+impl WinsorizeBounds {
+    #[inline]
+    pub fn check(&self) -> TResult<()> {
+        match self {
+            WinsorizeBounds::Quantile { lo, hi } => tensure!(
+                0. <= *lo && lo <= hi && *hi <= 1.,
+                "quantiles should satisfy 0 <= lo <= hi <= 1, find {} and {}",
+                lo,
+                hi
+            ),
+            WinsorizeBounds::Std(n) | WinsorizeBounds::Mad(n) => tensure!(
+                *n >= 0.,
+                "the number of deviations should not be negative, find {}",
+                n
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// linear interpolated quantile of sorted values
+#[inline]
+fn sorted_quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let idx = (sorted.len() - 1) as f64 * q;
+    let (lo, hi) = (idx.floor() as usize, idx.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (idx - lo as f64)
+}
+
+#[inline]
+fn sorted_valid(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut sorted: Vec<f64> = values.filter(|v| !v.is_nan()).collect();
+    <[f64]>::sort_unstable_by(&mut sorted, |a, b| a.total_cmp(b));
+    sorted
+}
+
+/// clip the values to the bounds computed by `method`, NaN values are excluded
+/// from the bounds and are kept as NaN. The values are unchanged if the bounds
+/// can not be computed.
+pub fn winsorize(values: &[f64], method: WinsorizeBounds) -> Vec<f64> {
+    let (lower, upper) = match method {
+        WinsorizeBounds::Quantile { lo, hi } => {
+            let sorted = sorted_valid(values.iter().copied());
+            (sorted_quantile(&sorted, lo), sorted_quantile(&sorted, hi))
+        }
+        WinsorizeBounds::Std(n) => {
+            let valid = || values.iter().copied().filter(|v| !v.is_nan());
+            let (count, sum) = valid().fold((0usize, 0.), |(c, s), v| (c + 1, s + v));
+            if count < 2 {
+                return values.to_owned();
+            }
+            let mean = sum / count as f64;
+            let var = valid().fold(0., |acc, v| acc + (v - mean).powi(2)) / (count - 1) as f64;
+            let std = var.sqrt();
+            (mean - n * std, mean + n * std)
+        }
+        WinsorizeBounds::Mad(n) => {
+            let sorted = sorted_valid(values.iter().copied());
+            let median = sorted_quantile(&sorted, 0.5);
+            let dev = sorted_valid(sorted.iter().map(|v| (v - median).abs()));
+            let mad = sorted_quantile(&dev, 0.5);
+            (median - n * mad, median + n * mad)
+        }
+    };
+    values
+        .iter()
+        .map(|&v| {
+            if v < lower {
+                lower
+            } else if v > upper {
+                upper
+            } else {
+                v
+            }
+        })
+        .collect()
+}
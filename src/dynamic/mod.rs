@@ -3,6 +3,7 @@ mod structs;
 #[macro_use]
 mod macros;
 
+pub use methods::*;
 pub use structs::*;
 
 #[cfg(feature = "py")]
@@ -426,6 +426,11 @@ impl<'a> DynArray<'a> {
         match_array!(self; Dynamic(v) => Ok(v.ndim()),).unwrap()
     }
 
+    #[inline]
+    pub fn shape(&self) -> Vec<usize> {
+        match_array!(self; Dynamic(a) => Ok(a.view().shape().to_vec()),).unwrap()
+    }
+
     #[inline]
     #[allow(clippy::clone_on_copy)]
     pub fn get(&self, index: usize) -> TResult<Scalar> {
@@ -477,6 +482,14 @@ impl<'a> DynArray<'a> {
         match_array!(self; Dynamic(v) => ArbArray::View(v.view()).into_series(),)
     }
 
+    /// cast the array to float, none values are NaN
+    #[inline]
+    pub fn to_f64_array(&self) -> TResult<ArrayD<f64>> {
+        match_array!(self; Numeric(a) => {
+            Ok(a.view().mapv(|v| v.to_opt().map_or(f64::NAN, |v| v.f64())))
+        },)
+    }
+
     #[inline]
     pub fn from_vec(vec: DynVec<'a>) -> TResult<DynArray<'a>> {
         vec.into_array()
@@ -41,6 +41,16 @@ pub trait NdArrayExt<T, D: Dimension> {
     where
         T: 'a,
         F: Fn(ArrayView1<'a, T>) -> Box<dyn TrustedLen<Item = U> + 'a> + Send + Sync;
+
+    fn calc_agg_func<'a, F, U: Send + Sync>(
+        &'a self,
+        f: F,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> ArrayD<U>
+    where
+        T: 'a,
+        F: Fn(ArrayView1<'a, T>) -> U + Send + Sync;
 }
 
 impl<T: Send + Sync, S: Data<Elem = T>, D: Dimension> NdArrayExt<T, D> for ArrayBase<S, D> {
@@ -75,7 +85,8 @@ impl<T: Send + Sync, S: Data<Elem = T>, D: Dimension> NdArrayExt<T, D> for Array
         }
     }
 
-    /// use trust_iter map function on each dimension of ndarray
+    /// use trust_iter map function on each dimension of ndarray, the lanes are
+    /// calculated in parallel if `par` is true
     fn calc_map_trust_iter_func<'a, F, U: Send + Sync + Clone>(
         &'a self,
         f: F,
@@ -107,4 +118,25 @@ impl<T: Send + Sync, S: Data<Elem = T>, D: Dimension> NdArrayExt<T, D> for Array
             .into_dimensionality()
             .unwrap()
     }
+
+    /// use aggregate function on each lane along the axis,
+    /// the axis is removed in the output array
+    fn calc_agg_func<'a, F, U: Send + Sync>(
+        &'a self,
+        f: F,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> ArrayD<U>
+    where
+        T: 'a,
+        F: Fn(ArrayView1<'a, T>) -> U + Send + Sync,
+    {
+        let axis = Axis(axis.unwrap_or(0));
+        let arr_zip = Zip::from(self.lanes(axis));
+        if par.unwrap_or(false) {
+            arr_zip.par_map_collect(f).into_dyn()
+        } else {
+            arr_zip.map_collect(f).into_dyn()
+        }
+    }
 }
@@ -32,7 +32,10 @@ macro_rules! match_series {
                 let $ca = $s.f64().unwrap();
                 $body
             }
-            _ => todo!(),
+            dtype => Err($crate::prelude::terr!(
+                "Not supported dtype {:?} for series",
+                dtype
+            )),
         }
     }};
 }
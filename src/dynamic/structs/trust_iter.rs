@@ -83,6 +83,17 @@ where
 // }
 
 impl<'a> DynTrustIter<'a> {
+    #[inline]
+    #[allow(unreachable_patterns)]
+    pub fn len(&self) -> usize {
+        crate::match_trust_iter!(self; Dynamic(i) => Ok(i.len()),).unwrap()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     #[inline]
     #[allow(unreachable_patterns)]
     pub fn collect_vec<'b>(self) -> TResult<DynVec<'b>> {
@@ -95,6 +106,14 @@ impl<'a> DynTrustIter<'a> {
         self.collect_vec()?.into_array()
     }
 
+    /// lazily skip the first `n` elements
+    #[inline]
+    #[allow(unreachable_patterns)]
+    pub fn skip(self, n: usize) -> Self {
+        let len = self.len().saturating_sub(n);
+        crate::match_trust_iter!(self; Dynamic(i) => Ok(i.skip(n).to_trust(len).into()),).unwrap()
+    }
+
     #[inline]
     pub fn chain(self, other: Self) -> Self {
         match_trust_iter!(self; Cast(i) => {
@@ -155,6 +174,21 @@ impl<'a> DynTrustIter<'a> {
         )
     }
 
+    /// collect the values as float, none values are NaN
+    #[inline]
+    pub fn collect_f64(self) -> TResult<Vec<f64>> {
+        match_trust_iter!(self; Numeric(e) => {
+            Ok(e.map(|v| v.to_opt().map_or(f64::NAN, |v| v.f64())).collect_trusted_to_vec())
+        },)
+    }
+
+    /// whether each value is not none
+    pub fn valid_mask(self) -> TResult<Vec<bool>> {
+        match_trust_iter!(self; (Normal | String | TimeRelated)(e) => {
+            Ok(e.map(|v| v.not_none()).collect_trusted_to_vec())
+        },)
+    }
+
     #[inline]
     pub fn collect<'b>(self, backend: Backend) -> TResult<Data<'b>> {
         match backend {
@@ -267,7 +267,7 @@ impl<'a> DynVec<'a> {
             },
             // zero copy but need mask
             (F32)(v) => {
-                let mask_iter = v.titer().map(|v| v.is_none());
+                let mask_iter = v.titer().map(|v| v.not_none());
                 let bitmap: Bitmap = mask_iter.collect_trusted();
                 match v {
                     Cow::Owned(v) => {
@@ -278,7 +278,7 @@ impl<'a> DynVec<'a> {
             },
             // zero copy but need mask
             (F64)(v) => {
-                let mask_iter = v.titer().map(|v| v.is_none());
+                let mask_iter = v.titer().map(|v| v.not_none());
                 let bitmap: Bitmap = mask_iter.collect_trusted();
                 match v {
                     Cow::Owned(v) => {
@@ -311,3 +311,22 @@ impl<'a> DynVec<'a> {
         )
     }
 }
+
+#[cfg(all(test, feature = "pl"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_series_validity() -> TResult<()> {
+        let v: DynVec = vec![1., f64::NAN, 3.].into();
+        let s = v.into_series()?;
+        assert_eq!(s.null_count(), 1);
+        assert!(s.is_null().get(1).unwrap());
+        assert_eq!(s.f64().unwrap().get(0), Some(1.));
+        let v: DynVec = vec![f32::NAN, 2.].into();
+        let s = v.into_series()?;
+        assert_eq!(s.null_count(), 1);
+        assert_eq!(s.f32().unwrap().get(1), Some(2.));
+        Ok(())
+    }
+}
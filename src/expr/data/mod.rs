@@ -306,6 +306,23 @@ impl<'a> Data<'a> {
         }
     }
 
+    /// consume the data as an iterator and apply `f` on it. Unlike `try_titer`,
+    /// this also works for a lazy iterator, and the data which can only be
+    /// iterated by reference (e.g. polars series) is borrowed instead.
+    #[inline]
+    pub fn with_titer<R>(self, f: impl FnOnce(DynTrustIter) -> TResult<R>) -> TResult<R> {
+        match self.try_into_iter() {
+            Ok(iter) => f(iter),
+            Err(data) => f(data.try_titer()?),
+        }
+    }
+
+    /// consume the data and collect the values as float, none values are NaN
+    #[inline]
+    pub fn collect_f64(self) -> TResult<Vec<f64>> {
+        self.with_titer(|iter| iter.collect_f64())
+    }
+
     /// try get a owned data, this will change the lifetime of data
     pub fn into_owned<'b>(self, backend: Option<Backend>) -> Result<Data<'b>, Self> {
         match self {
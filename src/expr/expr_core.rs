@@ -9,9 +9,6 @@ pub struct Expr {
     pub nodes: Vec<Node>,
 }
 
-unsafe impl Send for Expr {}
-unsafe impl Sync for Expr {}
-
 #[inline]
 pub fn s<S: Into<Symbol>>(symbol: S) -> Expr {
     let node: SelectNode = Into::<SelectNode>::into(symbol);
@@ -4,15 +4,10 @@ impl Expr {
     pub fn sum(self) -> Expr {
         let node = BaseNode {
             name: "sum",
+            elementwise: false,
             func: Arc::new(|data, _backend| match data.try_into_iter() {
                 Ok(iter) => Ok(iter.vsum()?.into()),
-                Err(data) => {
-                    if let Ok(iter) = data.try_titer() {
-                        Ok(iter.vsum()?.into())
-                    } else {
-                        todo!()
-                    }
-                }
+                Err(data) => Ok(data.try_titer()?.vsum()?.into()),
             }),
         };
         self.chain(node)
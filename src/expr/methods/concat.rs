@@ -0,0 +1,116 @@
+use crate::prelude::*;
+use tevec::ndarray::{concatenate, Axis};
+
+const INIT_VEC_LENGTH: usize = 100;
+
+#[allow(clippy::clone_on_copy)]
+pub fn concat_iter<'a, 'b, I: IntoIterator<Item = DynTrustIter<'a>>>(
+    is: I,
+    size_hint: Option<usize>,
+) -> TResult<DynVec<'b>> {
+    let mut iter = is.into_iter();
+    if let Some(i1) = iter.next() {
+        match_trust_iter!(i1; Cast(i1) => {
+            let iter_len = i1.len();
+            let mut vec = if let Some(size) = size_hint {
+                Vec::with_capacity(size * iter_len)
+            } else {
+                Vec::with_capacity(iter_len * INIT_VEC_LENGTH)
+            };
+            vec.extend(i1);
+            // a type hint iter so that we don't need to write code for each case
+            // TODO(Teamon): is there a better way to do auto type cast?
+            let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
+            for i in iter {
+                match_trust_iter!(i; Cast(i) => {
+                    vec.extend(i.cast_with(&type_hint));
+                    Ok(())
+                },)?;
+            }
+            Ok(vec.into())
+        },)
+    } else {
+        Ok(Default::default())
+    }
+}
+
+#[allow(clippy::clone_on_copy)]
+pub fn concat(es: Vec<Data>, axis: Option<usize>, backend: Backend) -> TResult<Data> {
+    let es_len = es.len();
+    if es.is_empty() {
+        return Ok(Default::default());
+    } else if es_len == 1 {
+        return Ok(es.into_iter().next().unwrap());
+    }
+    // fast path for concat iter
+    if Vec::get(&es, 0).unwrap().is_trust_iter() && Vec::get(&es, 1).unwrap().is_trust_iter() {
+        let is = es
+            .into_iter()
+            .map(|e| e.try_into_iter().unwrap())
+            .collect_trusted_to_vec();
+        return concat_iter(is, Some(es_len)).map(move |v| v.into_backend(backend).unwrap());
+    }
+
+    #[cfg(feature = "pl")]
+    if Vec::get(&es, 0).unwrap().is_series() {
+        // this is needed because we can't consume polars series and turn it into a trust iter
+        let s1 = Vec::get(&es, 0).unwrap();
+        let i1 = s1.try_titer().unwrap();
+        let out: Data<'_> = match_trust_iter!(i1; Cast(i1) => {
+            let iter_len = i1.len();
+            let mut vec = Vec::with_capacity(iter_len * es_len);
+            vec.extend(i1);
+            let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
+            for i in es.iter() {
+                match_trust_iter!(i.try_titer()?; Cast(i) => {
+                    // a type hint iter so that we don't need to write code for each case
+                    vec.extend(i.cast_with(&type_hint));
+                    Ok(())
+                },)?;
+            }
+            Ok(vec.into())
+        },)?;
+        return Ok(out.into_series().unwrap().into());
+    }
+    let len = es.len();
+
+    let mut es = es.into_iter();
+    let d1 = es.next().unwrap(); // first data
+    match d1.try_into_iter() {
+        Ok(i1) => {
+            let out: TResult<DynVec<'_>> = match_trust_iter!(i1; Cast(i1) => {
+                let iter_len = i1.len();
+                let mut vec = Vec::with_capacity(iter_len * es_len);
+                vec.extend(i1);
+                let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
+                for i in es {
+                    match_trust_iter!(i.try_into_iter().unwrap(); Cast(i) => {
+                        vec.extend(i.cast_with(&type_hint));
+                        Ok(())
+                    },)?;
+                }
+                Ok(vec.into())
+            },);
+            out.map(|v| v.into_backend(backend).unwrap())
+        }
+        Err(d1) => {
+            // multi dimensional array
+            match_array!(d1.into_array()?; Cast(a1) => {
+                // cast data to the same dtype
+                let mut data = Vec::with_capacity(len);
+                data.push(a1);
+                es.for_each(|e| {
+                    match_array!(e.into_array().unwrap(); Cast(a) => {
+                        data.push(a.cast_into());
+                        Ok(())
+                    },).unwrap();
+                });
+                // create a view of the data
+                let arr_views = data.iter().map(|a| a.view()).collect_trusted_to_vec();
+                let out = concatenate(Axis(axis.unwrap_or(0)), &arr_views).map_err(|_| terr!("concat array error"))?;
+                let out: DynArray = out.into();
+                Ok(out.into())
+            },)
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use crate::prelude::*;
+
+/// the group id of each label in order of first appearance, none labels have
+/// no group
+fn group_labels(groups: Data) -> TResult<Vec<Option<usize>>> {
+    let groups = groups.with_titer(|iter| iter.collect_vec())?;
+    let valid = groups.titer()?.valid_mask()?;
+    let (ids, _) = groups.titer()?.group_ids()?;
+    Ok(ids
+        .into_iter()
+        .zip(valid)
+        .map(|(id, valid)| valid.then_some(id))
+        .collect())
+}
+
+impl Expr {
+    fn cross_section(self, op: CrossSection, axis: Option<usize>, par: Option<bool>) -> Self {
+        let node = BaseNode {
+            name: op.name(),
+            elementwise: false,
+            func: Arc::new(move |data, backend| {
+                op.check()?;
+                let vec_out = |values: Vec<f64>| {
+                    let out: DynVec = cross_section(&values, op).into();
+                    out.into_backend(backend)
+                };
+                match data.try_into_iter() {
+                    Ok(iter) => vec_out(iter.collect_f64()?),
+                    Err(data) => {
+                        if let Ok(iter) = data.try_titer() {
+                            return vec_out(iter.collect_f64()?);
+                        }
+                        let arr: DynArray = data.into_array()?.cross_section(op, axis, par)?.into();
+                        Ok(arr.into())
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Average rank of each cross section, none values are kept as none and
+    /// the rank is divided by the count of valid values if `pct` is true.
+    ///
+    /// For a multi dimensional array, the cross sections are the lanes along
+    /// `axis`, which defaults to the last axis so that each row of a time x
+    /// asset panel is ranked. The lanes are calculated in parallel if `par` is true.
+    #[inline]
+    pub fn cs_rank(self, pct: bool, axis: Option<usize>, par: Option<bool>) -> Self {
+        self.cross_section(CrossSection::Rank { pct }, axis, par)
+    }
+
+    /// Subtract the mean and divide by the standard deviation of each cross
+    /// section, none values are excluded, see [`Expr::cs_rank`] for the axis.
+    #[inline]
+    pub fn cs_zscore(self, axis: Option<usize>, par: Option<bool>) -> Self {
+        self.cross_section(CrossSection::ZScore, axis, par)
+    }
+
+    /// Subtract the mean of each cross section, none values are excluded,
+    /// see [`Expr::cs_rank`] for the axis.
+    #[inline]
+    pub fn cs_demean(self, axis: Option<usize>, par: Option<bool>) -> Self {
+        self.cross_section(CrossSection::Demean, axis, par)
+    }
+
+    /// Scale each cross section so that the sum of the absolute values is one,
+    /// none values are excluded, see [`Expr::cs_rank`] for the axis.
+    #[inline]
+    pub fn cs_scale(self, axis: Option<usize>, par: Option<bool>) -> Self {
+        self.cross_section(CrossSection::Scale, axis, par)
+    }
+
+    /// Normalize each cross section so that the values sum to one, none values
+    /// are excluded, see [`Expr::cs_rank`] for the axis.
+    #[inline]
+    pub fn cs_normalize(self, axis: Option<usize>, par: Option<bool>) -> Self {
+        self.cross_section(CrossSection::Normalize, axis, par)
+    }
+
+    /// Clip the outliers to the bounds computed by `method`, none values are
+    /// excluded from the bounds and are kept as none.
+    ///
+    /// For a multi dimensional array, the bounds are computed on each lane along
+    /// `axis`, see [`Expr::cs_rank`] for the axis. Use [`Expr::over`] with a key
+    /// to winsorize each group. It is deliberately not `cs_` prefixed as it also
+    /// works on a 1-D series.
+    #[inline]
+    pub fn winsorize(
+        self,
+        method: WinsorizeBounds,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        self.cross_section(CrossSection::Winsorize(method), axis, par)
+    }
+
+    /// Subtract the mean of each group from the cross section, e.g. neutralize
+    /// a factor by industry. None values are excluded from the means and the
+    /// rows without a group label are none.
+    ///
+    /// For a multi dimensional array, the groups can be numeric labels of the
+    /// same shape or 1-D labels of any dtype aligned with every lane, see
+    /// [`Expr::cs_rank`] for the axis. For data in long format, use
+    /// [`Expr::over`] with the date key to neutralize each cross section.
+    pub fn neutralize(self, groups: Expr, axis: Option<usize>, par: Option<bool>) -> Self {
+        let node = CtxNode {
+            name: "neutralize",
+            func: Arc::new(move |data, ctx, backend| {
+                let groups = groups.eval(ctx, None)?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let groups = if matches!(&groups, Data::Array(g) if g.ndim() > 1) {
+                            groups.into_array()?
+                        } else {
+                            let ids: DynVec = group_labels(groups)?
+                                .into_iter()
+                                .map(|id| id.map_or(f64::NAN, |id| id as f64))
+                                .collect::<Vec<_>>()
+                                .into();
+                            DynArray::from_vec(ids)?
+                        };
+                        let out: DynArray = arr.neutralize(&groups, axis, par)?.into();
+                        Ok(out.into())
+                    }
+                    data => {
+                        let values = data.collect_f64()?;
+                        let ids = group_labels(groups)?;
+                        tensure!(values.len() == ids.len(), lm, values.len(), ids.len());
+                        let out: DynVec = group_demean(&values, &ids).into();
+                        out.into_backend(backend)
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Residual of the ordinary least squares regression of the cross section
+    /// on the numeric exposures with an intercept, e.g. neutralize a factor by
+    /// market cap. The rows where the data or any exposure is none are excluded.
+    ///
+    /// See [`Expr::neutralize`] for the shape of the exposures and the long format.
+    pub fn neutralize_regress(
+        self,
+        exposures: &[Expr],
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        let exposures = <[Expr]>::to_vec(exposures);
+        let node = CtxNode {
+            name: "neutralize_regress",
+            func: Arc::new(move |data, ctx, backend| {
+                let exposures = exposures
+                    .iter()
+                    .map(|e| e.eval(ctx, None))
+                    .collect::<TResult<Vec<_>>>()?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let exposures = exposures
+                            .into_iter()
+                            .map(|e| match e {
+                                Data::Array(_) => e.into_array(),
+                                e => DynArray::from_vec(e.collect_f64()?.into()),
+                            })
+                            .collect::<TResult<Vec<_>>>()?;
+                        let out: DynArray = arr.neutralize_regress(&exposures, axis, par)?.into();
+                        Ok(out.into())
+                    }
+                    data => {
+                        let values = data.collect_f64()?;
+                        let exposures = exposures
+                            .into_iter()
+                            .map(|e| {
+                                let e = e.collect_f64()?;
+                                tensure!(values.len() == e.len(), lm, values.len(), e.len());
+                                Ok(e)
+                            })
+                            .collect::<TResult<Vec<_>>>()?;
+                        let exposures: Vec<&[f64]> = exposures.iter().map(Vec::as_slice).collect();
+                        let out: DynVec = regress_residual(&values, &exposures).into();
+                        out.into_backend(backend)
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::methods::{eval_f64, eval_f64_2d};
+    use crate::{d2_array, d_vec};
+    use tevec::core::testing::assert_vec1d_equal_numeric;
+    use tevec::ndarray::prelude::*;
+
+    #[test]
+    fn test_cross_section() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new(d2_array![
+            [1., 3., nan, 2.],
+            [4., -2., 2., -4.],
+            [1., 1., 1., 1.]
+        ]);
+        for par in [false, true] {
+            let eval = |e: Expr| eval_f64_2d(e, &ctx);
+            let res = eval(s(0).cs_rank(false, None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[1., 3., nan, 2.], None);
+            assert_eq!(res.row(2).to_vec(), vec![2.5; 4]);
+            let res = eval(s(0).cs_rank(true, None, Some(par)))?;
+            assert_eq!(res.row(1).to_vec(), vec![1., 0.5, 0.75, 0.25]);
+            let res = eval(s(0).cs_demean(None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[-1., 1., nan, 0.], None);
+            assert_eq!(res.row(1).to_vec(), vec![4., -2., 2., -4.]);
+            let res = eval(s(0).cs_zscore(None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[-1., 1., nan, 0.], None);
+            // the standard deviation is zero
+            assert!(Iterator::all(&mut res.row(2).iter(), |v| v.is_nan()));
+            let res = eval(s(0).cs_scale(None, Some(par)))?;
+            assert_eq!(
+                res.row(1).to_vec(),
+                vec![1. / 3., -1. / 6., 1. / 6., -1. / 3.]
+            );
+            assert_eq!(res.row(2).to_vec(), vec![0.25; 4]);
+            let res = eval(s(0).cs_normalize(None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[1. / 6., 0.5, nan, 1. / 3.], None);
+            // the sum is zero
+            assert!(Iterator::all(&mut res.row(1).iter(), |v| v.is_nan()));
+            // the cross section along the first axis
+            let res = eval(s(0).cs_demean(Some(0), Some(par)))?;
+            assert_eq!(res.column(0).to_vec(), vec![-1., 2., -1.]);
+        }
+        // a vector is a single cross section
+        let ctx = Context::new(d_vec![1., nan, 3., 5.]);
+        let res = s(0)
+            .cs_zscore(None, None)
+            .eval(&ctx, None)?
+            .into_vec()?
+            .f64()?
+            .to_vec();
+        assert_vec1d_equal_numeric(&res, &[-1., nan, 0., 1.], None);
+        let ctx = Context::new(d2_array![[1., 2.]]);
+        assert!(s(0).cs_rank(false, Some(2), None).eval(&ctx, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_neutralize() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d2_array![[1., 2., 3., 5.], [4., nan, 6., 8.]].into(),
+                d_vec!["a".to_owned(), "a".into(), "b".into(), "b".into()].into(),
+                d2_array![[1, 1, 1, 2], [1, 2, 2, 2]].into(),
+                d2_array![[3., 5., 7., 9.], [1., 2., 1., 2.]].into(),
+                d_vec![1., 2., 3., 4.].into(),
+                d_vec![1., 1., 1., 1.].into(),
+                d_vec![1., 1., nan, 2.].into(),
+            ],
+            ["f", "ind", "ind2", "y", "size", "one", "ind3"],
+        );
+        for par in [false, true] {
+            let eval = |e: Expr| eval_f64_2d(e, &ctx);
+            // 1-D labels aligned with every row
+            let res = eval(s("f").neutralize(s("ind"), None, Some(par)))?;
+            assert_eq!(res.row(0).to_vec(), vec![-0.5, 0.5, -1., 1.]);
+            assert_vec1d_equal_numeric(&res.row(1).to_vec(), &[0., nan, -1., 1.], None);
+            let res = eval(s("f").neutralize(s("ind2"), None, Some(par)))?;
+            assert_eq!(res.row(0).to_vec(), vec![-1., 0., 1., 0.]);
+            assert_vec1d_equal_numeric(&res.row(1).to_vec(), &[0., nan, -1., 1.], None);
+            let res = eval(s("f").neutralize(s("ind3"), None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[-0.5, 0.5, nan, 0.], None);
+            assert_vec1d_equal_numeric(&res.row(1).to_vec(), &[0., nan, nan, 0.], None);
+            let res = eval(s("y").neutralize_regress(&[s("size")], None, Some(par)))?;
+            assert_vec1d_equal_numeric(&res.row(0).to_vec(), &[0.; 4], Some(1e-10));
+            assert_vec1d_equal_numeric(&res.row(1).to_vec(), &[-0.2, 0.6, -0.6, 0.2], Some(1e-10));
+            // collinear exposures
+            let res = eval(s("y").neutralize_regress(&[s("size"), s("one")], None, Some(par)))?;
+            assert!(Iterator::all(&mut res.iter(), |v| v.is_nan()));
+        }
+        assert!(s("f")
+            .neutralize_regress(&[s("y").head(1)], None, None)
+            .eval(&ctx, None)
+            .is_err());
+        // long format, neutralize each date
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1, 1, 1, 2, 2, 2].into(),
+                d_vec![
+                    "a".to_owned(),
+                    "a".into(),
+                    "b".into(),
+                    "a".into(),
+                    "b".into(),
+                    "b".into()
+                ]
+                .into(),
+                d_vec![1., 3., 5., 2., 4., 8.].into(),
+                d_vec![1., 2., 3., 1., 2., 3.].into(),
+                d_vec![1., 1., nan, 2., 2., 2.].into(),
+            ],
+            ["date", "ind", "f", "size", "ind3"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s("f").neutralize(s("ind"), None, None).over(&[s("date")]))?;
+        assert_eq!(res, vec![-1., 1., 0., 0., -2., 2.]);
+        let res = eval(
+            s("f")
+                .neutralize_regress(&[s("size")], None, None)
+                .over(&[s("date")]),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[0., 0., 0., 1. / 3., -2. / 3., 1. / 3.], Some(1e-10));
+        // lazy input and exposures
+        let res = eval(
+            s("f")
+                .abs()
+                .neutralize(s("ind"), None, None)
+                .over(&[s("date")]),
+        )?;
+        assert_eq!(res, vec![-1., 1., 0., 0., -2., 2.]);
+        let res = eval(
+            s("f")
+                .abs()
+                .neutralize_regress(&[s("size").abs()], None, None)
+                .over(&[s("date")]),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[0., 0., 0., 1. / 3., -2. / 3., 1. / 3.], Some(1e-10));
+        // the rows without a group label are none
+        let res = eval(s("f").neutralize(s("ind3"), None, None))?;
+        assert_vec1d_equal_numeric(
+            &res,
+            &[-1., 1., nan, -8. / 3., -2. / 3., 10. / 3.],
+            Some(1e-10),
+        );
+        assert!(eval(s("f").neutralize(s("ind").head(2), None, None)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_winsorize() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., nan].into(),
+                d_vec![1., 2., 3., 4., 100., nan, 1., 2., 3., 4., 5., nan].into(),
+                d_vec![1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2].into(),
+            ],
+            ["a", "b", "key"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let method = WinsorizeBounds::Quantile { lo: 0.1, hi: 0.9 };
+        let res = eval(s("a").winsorize(method, None, None))?;
+        let expect = [2., 2., 3., 4., 5., 6., 7., 8., 9., 10., 10., nan];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        // winsorize each group
+        let res = eval(
+            s("b")
+                .winsorize(WinsorizeBounds::Mad(1.), None, None)
+                .over(&[s("key")]),
+        )?;
+        let expect = [2., 2., 3., 4., 4., nan, 2., 2., 3., 4., 4., nan];
+        assert_vec1d_equal_numeric(&res, &expect, Some(1e-10));
+        let std = 2.5f64.sqrt();
+        let res = eval(
+            s("b")
+                .winsorize(WinsorizeBounds::Std(1.), None, None)
+                .over(&[s("key")]),
+        )?;
+        assert_vec1d_equal_numeric(
+            &<[f64]>::to_vec(&res[6..]),
+            &[3. - std, 2., 3., 4., 3. + std, nan],
+            Some(1e-10),
+        );
+        // winsorize each row of an array
+        let ctx = Context::new(d2_array![[1., 2., 3., 4., 100.], [-50., 1., 2., nan, 3.]]);
+        for par in [false, true] {
+            let res = s(0)
+                .winsorize(
+                    WinsorizeBounds::Quantile { lo: 0., hi: 0.75 },
+                    None,
+                    Some(par),
+                )
+                .eval(&ctx, None)?
+                .into_array()?
+                .f64()?;
+            let res: Array2<f64> = res.view().into_dimensionality().unwrap().to_owned();
+            assert_eq!(res.row(0).to_vec(), vec![1., 2., 3., 4., 4.]);
+            assert_vec1d_equal_numeric(&res.row(1).to_vec(), &[-50., 1., 2., nan, 2.25], None);
+        }
+        let method = WinsorizeBounds::Quantile { lo: 0.9, hi: 0.1 };
+        assert!(s(0).winsorize(method, None, None).eval(&ctx, None).is_err());
+        assert!(s(0)
+            .winsorize(WinsorizeBounds::Std(-1.), None, None)
+            .eval(&ctx, None)
+            .is_err());
+        // MAD is zero so every value is clipped to the median
+        let ctx = Context::new(d2_array![[2., 2., 2., -7., 9.], [1., 2., 3., 4., 5.]]);
+        let res = s(0)
+            .winsorize(WinsorizeBounds::Mad(3.), Some(1), None)
+            .eval(&ctx, None)?
+            .into_array()?
+            .f64()?;
+        let res: Array2<f64> = res.view().into_dimensionality().unwrap().to_owned();
+        assert_eq!(res.row(0).to_vec(), vec![2.; 5]);
+        assert_eq!(res.row(1).to_vec(), vec![1., 2., 3., 4., 5.]);
+        let ctx = Context::new(d_vec![5., 5., nan, 5., 1., 5.]);
+        let res = eval_f64(s(0).winsorize(WinsorizeBounds::Mad(1.), None, None), &ctx)?;
+        assert_vec1d_equal_numeric(&res, &[5., 5., nan, 5., 5., 5.], None);
+        Ok(())
+    }
+}
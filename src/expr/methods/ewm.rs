@@ -0,0 +1,268 @@
+use crate::prelude::*;
+
+#[derive(Clone)]
+pub struct Ewm {
+    decay: EwmDecay,
+    adjust: bool,
+    min_periods: usize,
+    ignore_nulls: bool,
+    axis: Option<usize>,
+    par: Option<bool>,
+    expr: Expr,
+}
+
+impl Ewm {
+    /// Divide by the decaying adjustment factor in the beginning periods,
+    /// otherwise the result is calculated recursively, defaults to true
+    #[inline]
+    pub fn adjust(mut self, adjust: bool) -> Self {
+        self.adjust = adjust;
+        self
+    }
+
+    /// Minimum number of observations required to have a value, defaults to 1
+    #[inline]
+    pub fn min_periods(mut self, min_periods: usize) -> Self {
+        self.min_periods = min_periods;
+        self
+    }
+
+    /// Ignore none values when calculating the weights, otherwise the weights
+    /// are based on the absolute positions, defaults to false
+    #[inline]
+    pub fn ignore_nulls(mut self, ignore_nulls: bool) -> Self {
+        self.ignore_nulls = ignore_nulls;
+        self
+    }
+
+    /// The axis to calculate along for multi dimensional arrays, defaults to 0
+    #[inline]
+    pub fn axis(mut self, axis: usize) -> Self {
+        self.axis = Some(axis);
+        self
+    }
+
+    /// Whether to calculate the lanes of a multi dimensional array in parallel
+    #[inline]
+    pub fn par(mut self, par: bool) -> Self {
+        self.par = Some(par);
+        self
+    }
+
+    /// the options are validated when the expression is evaluated
+    #[inline]
+    fn into_parts(self) -> (impl Fn() -> TResult<EwmOptions> + Copy, Expr) {
+        let Ewm {
+            decay,
+            adjust,
+            min_periods,
+            ignore_nulls,
+            expr,
+            ..
+        } = self;
+        let options = move || {
+            Ok(EwmOptions {
+                alpha: decay.alpha()?,
+                adjust,
+                ignore_nulls,
+                min_periods,
+            })
+        };
+        (options, expr)
+    }
+
+    /// Exponentially weighted statistic, none values are skipped
+    /// and the output is always float
+    pub fn agg(self, agg: EwmAgg) -> Expr {
+        let (axis, par) = (self.axis, self.par);
+        let (options, expr) = self.into_parts();
+        let node = BaseNode {
+            name: agg.name(),
+            elementwise: false,
+            func: Arc::new(move |data, backend| {
+                let opt = options()?;
+                match data.try_into_iter() {
+                    Ok(iter) => Ok(iter.ewm(agg, opt)?.into()),
+                    Err(data) => {
+                        if let Ok(iter) = data.try_titer() {
+                            return iter.ewm(agg, opt)?.collect(backend);
+                        }
+                        let arr: DynArray = data.into_array()?.ewm(agg, opt, axis, par)?.into();
+                        Ok(arr.into())
+                    }
+                }
+            }),
+        };
+        expr.chain(node)
+    }
+
+    #[inline]
+    pub fn mean(self) -> Expr {
+        self.agg(EwmAgg::Mean)
+    }
+
+    /// Exponentially weighted unbiased standard deviation
+    #[inline]
+    pub fn std(self) -> Expr {
+        self.agg(EwmAgg::Std)
+    }
+
+    /// Exponentially weighted unbiased variance
+    #[inline]
+    pub fn var(self) -> Expr {
+        self.agg(EwmAgg::Var)
+    }
+
+    /// Exponentially weighted statistic with `other`, if the data is a multi
+    /// dimensional array, `other` can be a 1-D array aligned with each lane or
+    /// an array of the same shape
+    pub fn pair_agg(self, other: Expr, agg: EwmPairAgg) -> Expr {
+        let (axis, par) = (self.axis, self.par);
+        let (options, expr) = self.into_parts();
+        let node = CtxNode {
+            name: agg.name(),
+            func: Arc::new(move |data, ctx, backend| {
+                let opt = options()?;
+                let other = other.eval(ctx, None)?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let other = match other {
+                            Data::Array(_) => other.into_array()?,
+                            other => {
+                                let other: DynVec = other.collect_f64()?.into();
+                                DynArray::from_vec(other)?
+                            }
+                        };
+                        let out: DynArray = arr.ewm_pair(&other, agg, opt, axis, par)?.into();
+                        Ok(out.into())
+                    }
+                    data => {
+                        // both inputs are consumed so that they can be lazy
+                        let other = other.collect_f64()?;
+                        data.with_titer(|iter| {
+                            iter.ewm_pair(other.into_iter().into(), agg, opt)?
+                                .collect(backend)
+                        })
+                    }
+                }
+            }),
+        };
+        expr.chain(node)
+    }
+
+    /// Exponentially weighted correlation with `other`
+    #[inline]
+    pub fn corr(self, other: Expr) -> Expr {
+        self.pair_agg(other, EwmPairAgg::Corr)
+    }
+
+    /// Exponentially weighted unbiased covariance with `other`
+    #[inline]
+    pub fn cov(self, other: Expr) -> Expr {
+        self.pair_agg(other, EwmPairAgg::Cov)
+    }
+}
+
+impl Expr {
+    /// Exponentially weighted window, the decay is specified by span,
+    /// halflife, center of mass or alpha, the same as pandas.
+    #[inline]
+    pub fn ewm(self, decay: EwmDecay) -> Ewm {
+        Ewm {
+            decay,
+            adjust: true,
+            min_periods: 1,
+            ignore_nulls: false,
+            axis: None,
+            par: None,
+            expr: self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::methods::eval_f64;
+    use crate::{d2_array, d_vec};
+    use tevec::core::testing::assert_vec1d_equal_numeric;
+    use tevec::ndarray::Axis;
+
+    #[test]
+    fn test_ewm() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1., 2., nan, 4., 3.].into(),
+                d_vec![2., 1., 3., 5., 4.].into(),
+            ],
+            ["x", "y"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        // the same as pandas
+        let res = eval(s("x").ewm(EwmDecay::Com(1.)).mean())?;
+        let expect = [
+            1.,
+            1.6666666666666667,
+            1.6666666666666667,
+            3.3636363636363638,
+            3.148148148148148,
+        ];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("x").ewm(EwmDecay::Alpha(0.5)).ignore_nulls(true).mean())?;
+        let expect = [1., 1.6666666666666667, 1.6666666666666667, 3., 3.];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("x").ewm(EwmDecay::Span(3.)).adjust(false).mean())?;
+        let expect = [1., 1.5, 1.5, 3.1666666666666665, 3.083333333333333];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("y").ewm(EwmDecay::Com(1.)).var())?;
+        let expect = [
+            nan,
+            0.5,
+            1.3571428571428572,
+            3.5285714285714294,
+            1.674193548387097,
+        ];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("y").ewm(EwmDecay::Com(1.)).min_periods(3).std())?;
+        let expect = [
+            nan,
+            nan,
+            1.164964745021435,
+            1.878449208408742,
+            1.2939063136050837,
+        ];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("x").ewm(EwmDecay::Com(1.)).cov(s("y")))?;
+        let expect = [nan, -0.5, -0.5, 3.8076923076923084, 1.202970297029703];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s("x").ewm(EwmDecay::Com(1.)).corr(s("y")))?;
+        let expect = [nan, -1., -1., 0.9278305692406299, 0.8975103686746204];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        // lazy input
+        let res = eval(s("x").abs().ewm(EwmDecay::Com(1.)).corr(s("y").abs()))?;
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        // calculate along an axis
+        let ctx = Context::new(d2_array![[1., 2.], [2., 1.], [nan, 3.], [4., 5.], [3., 4.]]);
+        for par in [false, true] {
+            let res = s(0)
+                .ewm(EwmDecay::Com(1.))
+                .par(par)
+                .mean()
+                .eval(&ctx, None)?
+                .into_array()?
+                .f64()?;
+            let res: Vec<f64> = res.view().index_axis(Axis(1), 0).iter().cloned().collect();
+            let expect = [
+                1.,
+                1.6666666666666667,
+                1.6666666666666667,
+                3.3636363636363638,
+                3.148148148148148,
+            ];
+            assert_vec1d_equal_numeric(&res, &expect, None);
+        }
+        assert!(eval(s("x").ewm(EwmDecay::Alpha(0.)).mean()).is_err());
+        Ok(())
+    }
+}
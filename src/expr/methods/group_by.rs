@@ -0,0 +1,546 @@
+use super::concat::concat;
+use super::select::{data_len, take_data};
+use crate::prelude::*;
+
+/// The rows of a context grouped by the values of the key expressions,
+/// see [`Context::group_by`]
+pub struct GroupBy<'c, 'a> {
+    ctx: &'c Context<'a>,
+    keys: Vec<Expr>,
+}
+
+impl<'a> Context<'a> {
+    /// Group the rows by the values of the key expressions, string, integer,
+    /// bool and datetime keys are supported and none values form their own group
+    #[inline]
+    pub fn group_by<'c>(&'c self, keys: &[Expr]) -> GroupBy<'c, 'a> {
+        GroupBy {
+            ctx: self,
+            keys: <[Expr]>::to_vec(keys),
+        }
+    }
+}
+
+/// the output name of the expression, defaults to the index of the column
+#[inline]
+fn output_name(expr: &Expr, i: usize) -> Arc<str> {
+    expr.name.clone().unwrap_or_else(|| i.to_string().into())
+}
+
+/// combine the group ids of the keys, return the group id of each row and the
+/// index of the first row of each group, the groups are in order of first appearance
+fn combine_group_ids<I>(keys: I) -> TResult<(Vec<usize>, Vec<usize>)>
+where
+    I: IntoIterator<Item = TResult<(Vec<usize>, Vec<usize>)>>,
+{
+    let mut group: Option<(Vec<usize>, Vec<usize>)> = None;
+    for key in keys {
+        let (ids, first) = key?;
+        group = Some(match group {
+            None => (ids, first),
+            Some((prev, _)) => {
+                tensure!(prev.len() == ids.len(), lm, prev.len(), ids.len());
+                // combine the ids of the previous keys and this key
+                let n = first.len();
+                let combined: Vec<usize> = prev
+                    .into_iter()
+                    .zip(ids)
+                    .map(|(p, id)| p * n + id)
+                    .collect();
+                hash_group_ids(&combined)
+            }
+        });
+    }
+    group.ok_or_else(|| terr!("group by needs at least one key"))
+}
+
+/// the indices of the rows of each group
+fn group_rows(ids: &[usize], n_groups: usize) -> Vec<Vec<usize>> {
+    let mut groups = vec![Vec::new(); n_groups];
+    ids.iter()
+        .enumerate()
+        .for_each(|(i, id)| groups[*id].push(i));
+    groups
+}
+
+/// the context of the rows of a group, the columns aligned with the keys
+/// are taken and the others are shared by all groups
+fn group_context<'a>(
+    ctx: &Context<'a>,
+    idx: &[usize],
+    n_rows: usize,
+    backend: Backend,
+) -> TResult<Context<'a>> {
+    let data = ctx
+        .data
+        .iter()
+        .map(|d| match data_len(d, 0) {
+            Ok(len) if len == n_rows => {
+                take_data(d.clone(), idx.to_owned(), 0, backend)?.into_result(Some(backend))
+            }
+            _ => Ok(d.clone()),
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    Ok(Context {
+        data,
+        backend: ctx.backend,
+        col_map: ctx.col_map.clone(),
+    })
+}
+
+/// evaluate the expression in the context and take the ownership of the result
+#[inline]
+fn eval_owned<'b>(expr: &Expr, ctx: &Context<'_>, backend: Backend) -> TResult<Data<'b>> {
+    expr.eval(ctx, Some(backend))?
+        .into_owned(Some(backend))
+        .map_err(|_| terr!("the result of group can not be owned"))
+}
+
+/// evaluate each aggregation expression on the rows of each group, every
+/// expression should return a single value for a group
+fn agg_groups<'b, I: IntoIterator<Item = Vec<usize>>>(
+    ctx: &Context<'_>,
+    groups: I,
+    n_rows: usize,
+    exprs: &[Expr],
+    backend: Backend,
+) -> TResult<Vec<Data<'b>>> {
+    let mut results: Vec<Vec<Data<'b>>> = vec![Vec::new(); exprs.len()];
+    for idx in groups {
+        let ctx = group_context(ctx, &idx, n_rows, backend)?;
+        for (expr, res) in exprs.iter().zip(&mut results) {
+            let data = eval_owned(expr, &ctx, backend)?;
+            let len = data.try_titer()?.len();
+            tensure!(
+                len == 1,
+                "aggregation should return a single value for each group, got {}",
+                len
+            );
+            res.push(data);
+        }
+    }
+    results
+        .into_iter()
+        .map(|res| concat(res, None, backend))
+        .collect()
+}
+
+impl<'c, 'a> GroupBy<'c, 'a> {
+    /// Evaluate each aggregation expression on the rows of each group, the groups
+    /// are in order of first appearance. Every expression should return a single
+    /// value for a group.
+    ///
+    /// Return a new context with the key columns followed by the aggregated columns,
+    /// the columns are named by the expressions or their indices.
+    pub fn agg<'b>(&self, exprs: &[Expr]) -> TResult<Context<'b>> {
+        let backend = self.ctx.backend.unwrap_or_default();
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| key.eval(self.ctx, Some(backend)))
+            .collect::<TResult<Vec<_>>>()?;
+        let (ids, first) = combine_group_ids(keys.iter().map(|k| k.try_titer()?.group_ids()))?;
+        let mut out = Vec::with_capacity(keys.len() + exprs.len());
+        for key in keys {
+            let key = take_data(key, first.clone(), 0, backend)?;
+            out.push(
+                key.into_owned(Some(backend))
+                    .map_err(|_| terr!("group key can not be owned"))?,
+            );
+        }
+        let groups = group_rows(&ids, first.len());
+        out.extend(agg_groups(self.ctx, groups, ids.len(), exprs, backend)?);
+        let names: Vec<Arc<str>> = self
+            .keys
+            .iter()
+            .chain(exprs)
+            .enumerate()
+            .map(|(i, e)| output_name(e, i))
+            .collect();
+        Ok(Context::new_from_data_column(out, names))
+    }
+}
+
+/// The rows of a context grouped into regular time windows, see
+/// [`Context::group_by_dynamic`]
+#[cfg(all(feature = "rolling", feature = "time"))]
+pub struct DynamicGroupBy<'c, 'a> {
+    ctx: &'c Context<'a>,
+    time: Expr,
+    every: TimeDelta,
+    period: Option<TimeDelta>,
+    offset: Option<TimeDelta>,
+    closed: ClosedWindow,
+    label: WindowLabel,
+}
+
+#[cfg(all(feature = "rolling", feature = "time"))]
+impl<'a> Context<'a> {
+    /// Group the rows into regular windows by the time expression, which should
+    /// be sorted in ascending order. A window starts every `every` from the first
+    /// time truncated by `every`, e.g. resample tick data into 1 minute bars.
+    #[inline]
+    pub fn group_by_dynamic<'c, P: Into<TimeDelta>>(
+        &'c self,
+        time: Expr,
+        every: P,
+    ) -> DynamicGroupBy<'c, 'a> {
+        DynamicGroupBy {
+            ctx: self,
+            time,
+            every: every.into(),
+            period: None,
+            offset: None,
+            closed: ClosedWindow::Left,
+            label: WindowLabel::Left,
+        }
+    }
+}
+
+#[cfg(all(feature = "rolling", feature = "time"))]
+impl<'c, 'a> DynamicGroupBy<'c, 'a> {
+    /// The length of each window, defaults to `every`
+    #[inline]
+    pub fn period<P: Into<TimeDelta>>(mut self, period: P) -> Self {
+        self.period = Some(period.into());
+        self
+    }
+
+    /// Shift the start of the windows
+    #[inline]
+    pub fn offset<P: Into<TimeDelta>>(mut self, offset: P) -> Self {
+        self.offset = Some(offset.into());
+        self
+    }
+
+    /// Which sides of the window are closed, defaults to left
+    #[inline]
+    pub fn closed(mut self, closed: ClosedWindow) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// How to label the windows, defaults to the start of the window
+    #[inline]
+    pub fn label(mut self, label: WindowLabel) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// the row bounds and the label data of the windows, and the number of rows
+    #[allow(clippy::type_complexity)]
+    fn windows<'b>(&self, backend: Backend) -> TResult<(Vec<(usize, usize)>, Data<'b>, usize)> {
+        let times = self.time.eval(self.ctx, Some(backend))?;
+        let iter = times.try_titer()?;
+        let n_rows = iter.len();
+        let period = self.period.unwrap_or(self.every);
+        let (bounds, labels) =
+            iter.dynamic_window_bounds(self.every, period, self.offset, self.closed, self.label)?;
+        Ok((bounds, labels.into_backend(backend)?, n_rows))
+    }
+
+    /// the name of the label column
+    #[inline]
+    fn label_name(&self) -> Arc<str> {
+        output_name(&self.time, 0)
+    }
+
+    /// Evaluate each aggregation expression on the rows of each window, every
+    /// expression should return a single value for a window.
+    ///
+    /// Return a new context with the label column followed by the aggregated columns.
+    pub fn agg<'b>(&self, exprs: &[Expr]) -> TResult<Context<'b>> {
+        let backend = self.ctx.backend.unwrap_or_default();
+        let (bounds, labels, n_rows) = self.windows(backend)?;
+        let groups = bounds.into_iter().map(|(lo, hi)| (lo..hi).collect());
+        let mut out = vec![labels];
+        out.extend(agg_groups(self.ctx, groups, n_rows, exprs, backend)?);
+        let names: Vec<Arc<str>> = std::iter::once(self.label_name())
+            .chain(exprs.iter().enumerate().map(|(i, e)| output_name(e, i + 1)))
+            .collect();
+        Ok(Context::new_from_data_column(out, names))
+    }
+
+    /// The open, high, low and close values of the expression in each window,
+    /// none values are ignored.
+    ///
+    /// Return a new context with the label column followed by the `open`,
+    /// `high`, `low` and `close` columns.
+    pub fn ohlc<'b>(&self, expr: Expr) -> TResult<Context<'b>> {
+        let backend = self.ctx.backend.unwrap_or_default();
+        let (bounds, labels, n_rows) = self.windows(backend)?;
+        let values = expr
+            .eval(self.ctx, Some(backend))?
+            .try_titer()?
+            .collect_f64()?;
+        tensure!(values.len() == n_rows, lm, values.len(), n_rows);
+        let mut ohlc: [Vec<f64>; 4] = std::array::from_fn(|_| Vec::with_capacity(bounds.len()));
+        for (lo, hi) in bounds {
+            let valid = values[lo..hi].iter().copied().filter(|v| !v.is_nan());
+            let (mut open, mut high, mut low, mut close) = (f64::NAN, f64::NAN, f64::NAN, f64::NAN);
+            for v in valid {
+                if open.is_nan() {
+                    (open, high, low) = (v, v, v);
+                }
+                high = high.max(v);
+                low = low.min(v);
+                close = v;
+            }
+            for (col, v) in ohlc.iter_mut().zip([open, high, low, close]) {
+                col.push(v);
+            }
+        }
+        let mut out = vec![labels];
+        for col in ohlc {
+            let col: DynVec = col.into();
+            out.push(col.into_backend(backend)?);
+        }
+        let names = [
+            self.label_name(),
+            "open".into(),
+            "high".into(),
+            "low".into(),
+            "close".into(),
+        ];
+        Ok(Context::new_from_data_column(out, names))
+    }
+}
+
+impl Expr {
+    /// Evaluate the expression separately within each partition of the rows
+    /// grouped by `keys` and map the results back to the original row order.
+    ///
+    /// The result of a partition should be a single value, which is broadcast
+    /// to the rows of the partition, or have the same length as the partition.
+    pub fn over(self, keys: &[Expr]) -> Expr {
+        let Some((first_key, other_keys)) = keys.split_first() else {
+            let node = BaseNode {
+                name: "over",
+                elementwise: false,
+                func: Arc::new(|_, _| tbail!("over needs at least one key")),
+            };
+            return self.chain(node);
+        };
+        let other_keys = <[Expr]>::to_vec(other_keys);
+        let name = self.name.clone();
+        let node = CtxNode {
+            name: "over",
+            func: Arc::new(move |first_key, ctx, backend| {
+                // the keys are consumed so that they can be computed lazily
+                let other_ids = other_keys.iter().map(|key| {
+                    key.eval(ctx, Some(backend))?
+                        .with_titer(|iter| iter.group_ids())
+                });
+                let first_ids = first_key.with_titer(|iter| iter.group_ids());
+                let (ids, first) = combine_group_ids(std::iter::once(first_ids).chain(other_ids))?;
+                let n_rows = ids.len();
+                let groups = group_rows(&ids, first.len());
+                // the position of each row in the concatenated results
+                let mut pos = vec![0; n_rows];
+                let mut offset = 0;
+                let mut results = Vec::with_capacity(groups.len());
+                for idx in groups {
+                    let group_ctx = group_context(ctx, &idx, n_rows, backend)?;
+                    let data = eval_owned(&self, &group_ctx, backend)?;
+                    let len = data.try_titer()?.len();
+                    if len == 1 {
+                        idx.iter().for_each(|i| pos[*i] = offset);
+                    } else {
+                        tensure!(
+                            len == idx.len(),
+                            "the result of a partition should be a single value or have the \
+                            same length as the partition, got {} and {}",
+                            len,
+                            idx.len()
+                        );
+                        idx.iter()
+                            .enumerate()
+                            .for_each(|(k, i)| pos[*i] = offset + k);
+                    }
+                    offset += len;
+                    results.push(data);
+                }
+                let out = concat(results, None, backend)?;
+                take_data(out, pos, 0, backend)
+            }),
+        };
+        let mut expr = first_key.clone().chain(node);
+        expr.name = name;
+        expr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d_vec;
+    use crate::expr::methods::eval_f64;
+
+    #[test]
+    fn test_group_by() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![
+                    "a".to_owned(),
+                    "b".into(),
+                    "a".into(),
+                    "c".into(),
+                    "b".into()
+                ]
+                .into(),
+                d_vec![1, 1, 2, 1, 1].into(),
+                d_vec![1., 2., 3., 4., 5.].into(),
+            ],
+            ["k1", "k2", "v"],
+        );
+        let out = ctx
+            .group_by(&[s("k1")])
+            .agg(&[s("v").sum(), s("v").n_unique().alias("n")])?;
+        assert!(out.get("k1").is_ok() && out.get("v").is_ok() && out.get("n").is_ok());
+        let mut cols = out.data.into_iter();
+        let keys = cols.next().unwrap().into_vec()?.string()?.to_vec();
+        assert_eq!(keys, vec!["a", "b", "c"]);
+        let res = cols.next().unwrap().into_vec()?.f64()?.to_vec();
+        assert_eq!(res, vec![4., 7., 4.]);
+        let res = cols.next().unwrap().into_vec()?.usize()?.to_vec();
+        assert_eq!(res, vec![2, 2, 1]);
+        // group by multiple keys
+        let out = ctx.group_by(&[s("k1"), s("k2")]).agg(&[s("v").sum()])?;
+        let mut cols = out.data.into_iter().skip(1);
+        let k2 = cols.next().unwrap().into_vec()?.i32()?.to_vec();
+        assert_eq!(k2, vec![1, 1, 2, 1]);
+        let res = cols.next().unwrap().into_vec()?.f64()?.to_vec();
+        assert_eq!(res, vec![1., 7., 3., 4.]);
+        // the aggregation should return a single value
+        assert!(ctx.group_by(&[s("k1")]).agg(&[s("v")]).is_err());
+        assert!(ctx.group_by(&[]).agg(&[s("v").sum()]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_over() -> TResult<()> {
+        use tevec::core::testing::assert_vec1d_equal_numeric;
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1, 1, 1, 2, 2, 2].into(),
+                d_vec![
+                    "a".to_owned(),
+                    "b".into(),
+                    "c".into(),
+                    "a".into(),
+                    "b".into(),
+                    "c".into()
+                ]
+                .into(),
+                d_vec![1., 2., 3., 4., 5., 6.].into(),
+                d_vec![3., 1., 2., 1., 2., 3.].into(),
+            ],
+            ["date", "ticker", "v", "v2"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        // shift within each ticker
+        let res = eval(s("v").vshift(lit(1), None, None, None).over(&[s("ticker")]))?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, 1., 2., 3.], None);
+        // rank within each date
+        let res = eval(
+            s("v2")
+                .rank(RankMethod::Average, false, false, None, None)
+                .over(&[s("date")]),
+        )?;
+        assert_eq!(res, vec![3., 1., 2., 1., 2., 3.]);
+        // aggregation is broadcast within each group
+        let res = eval(s("v").sum().over(&[s("ticker")]))?;
+        assert_eq!(res, vec![5., 7., 9., 5., 7., 9.]);
+        let res = eval(s("v").sum().over(&[s("ticker"), s("date")]))?;
+        assert_eq!(res, vec![1., 2., 3., 4., 5., 6.]);
+        // computed keys
+        let res = eval(s("v").sum().over(&[s("date").abs()]))?;
+        assert_eq!(res, vec![6., 6., 6., 15., 15., 15.]);
+        let res = eval(s("v").sum().over(&[s("ticker"), s("date").abs()]))?;
+        assert_eq!(res, vec![1., 2., 3., 4., 5., 6.]);
+        #[cfg(feature = "rolling")]
+        {
+            let res = eval(s("v").rolling(2).apply(s(0).sum()).over(&[s("ticker")]))?;
+            assert_eq!(res, vec![1., 2., 3., 5., 7., 9.]);
+        }
+        assert!(eval(s("v").head(2).over(&[s("date")])).is_err());
+        assert!(eval(s("v").over(&[])).is_err());
+        Ok(())
+    }
+
+    #[cfg(all(feature = "rolling", feature = "time"))]
+    #[test]
+    fn test_group_by_dynamic() -> TResult<()> {
+        use tevec::core::testing::assert_vec1d_equal_numeric;
+        let sec = |s: i64| DateTime::<unit::Millisecond>::new(s * 1000);
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![sec(10), sec(20), sec(50), sec(65), sec(130), sec(170)].into(),
+                d_vec![3., 5., 1., 4., f64::NAN, 2.].into(),
+            ],
+            ["t", "v"],
+        );
+        let labels = |out: Context| -> TResult<Vec<DateTime<unit::Millisecond>>> {
+            Ok(out
+                .data
+                .into_iter()
+                .next()
+                .unwrap()
+                .into_vec()?
+                .datetime_ms()?
+                .to_vec())
+        };
+        let out = ctx.group_by_dynamic(s("t"), "1m").ohlc(s("v"))?;
+        assert!(out.get("t").is_ok() && out.get("open").is_ok() && out.get("close").is_ok());
+        let mut cols = out.data.into_iter();
+        let t = cols.next().unwrap().into_vec()?.datetime_ms()?.to_vec();
+        assert_eq!(t, vec![sec(0), sec(60), sec(120)]);
+        let ohlc = cols
+            .map(|c| Ok(c.into_vec()?.f64()?.to_vec()))
+            .collect::<TResult<Vec<_>>>()?;
+        assert_eq!(ohlc[0], vec![3., 4., 2.]);
+        assert_eq!(ohlc[1], vec![5., 4., 2.]);
+        assert_eq!(ohlc[2], vec![1., 4., 2.]);
+        assert_eq!(ohlc[3], vec![1., 4., 2.]);
+        // ordinary aggregations
+        let out = ctx
+            .group_by_dynamic(s("t"), "1m")
+            .agg(&[s("v").sum(), s("v").n_unique().alias("n")])?;
+        let mut cols = out.data.into_iter().skip(1);
+        let res = cols.next().unwrap().into_vec()?.f64()?.to_vec();
+        assert_eq!(res, vec![9., 4., 2.]);
+        let res = cols.next().unwrap().into_vec()?.usize()?.to_vec();
+        assert_eq!(res, vec![3, 1, 2]);
+        // overlapping windows
+        let out = ctx
+            .group_by_dynamic(s("t"), "1m")
+            .period("2m")
+            .agg(&[s("v").sum()])?;
+        let res = out.data.into_iter().nth(1).unwrap().into_vec()?.f64()?;
+        assert_vec1d_equal_numeric(&res.to_vec(), &[13., 6., 2.], None);
+        // labels and offset
+        let out = ctx
+            .group_by_dynamic(s("t"), "1m")
+            .label(WindowLabel::Right)
+            .agg(&[s("v").sum()])?;
+        assert_eq!(labels(out)?, vec![sec(60), sec(120), sec(180)]);
+        let out = ctx
+            .group_by_dynamic(s("t"), "1m")
+            .label(WindowLabel::DataPoint)
+            .agg(&[s("v").sum()])?;
+        assert_eq!(labels(out)?, vec![sec(10), sec(65), sec(130)]);
+        let out = ctx
+            .group_by_dynamic(s("t"), "1m")
+            .offset("30s")
+            .agg(&[s("v").sum()])?;
+        assert_eq!(labels(out)?, vec![sec(-30), sec(30), sec(90), sec(150)]);
+        // the time column should be sorted
+        let ctx = Context::new_from_data_column(
+            vec![d_vec![sec(20), sec(10)].into(), d_vec![1., 2.].into()],
+            ["t", "v"],
+        );
+        assert!(ctx.group_by_dynamic(s("t"), "1m").ohlc(s("v")).is_err());
+        Ok(())
+    }
+}
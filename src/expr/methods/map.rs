@@ -5,6 +5,7 @@ impl Expr {
     pub fn abs(self) -> Self {
         let node = BaseNode {
             name: "abs",
+            elementwise: true,
             func: Arc::new(|data, backend| match data.try_into_iter() {
                 Ok(iter) => Ok(iter.abs()?.into()),
                 Err(data) => {
@@ -29,6 +30,7 @@ impl Expr {
     pub fn vabs(self) -> Self {
         let node = BaseNode {
             name: "vabs",
+            elementwise: true,
             func: Arc::new(|data, backend| match data.try_into_iter() {
                 Ok(iter) => Ok(iter.vabs()?.into()),
                 Err(data) => {
@@ -87,4 +89,43 @@ impl Expr {
         };
         self.chain(node)
     }
+
+    /// Rank the data, none values are kept as none. For array with ndim > 1,
+    /// the data is ranked along `axis`.
+    pub fn rank(
+        self,
+        method: RankMethod,
+        descending: bool,
+        pct: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        let node = BaseNode {
+            name: "rank",
+            elementwise: false,
+            func: Arc::new(move |data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.rank(method, descending, pct)?.into()),
+                Err(data) => {
+                    if let Ok(iter) = data.try_titer() {
+                        return iter.rank(method, descending, pct)?.collect(backend);
+                    }
+                    match_array!(
+                        data.into_array()?;
+                        (Normal | String | Time)(arr) => {
+                            let arr: DynArray = arr
+                                .view()
+                                .calc_map_trust_iter_func(
+                                    move |a| Box::new(rank_by(a.titer(), method, descending, pct).into_iter()),
+                                    axis,
+                                    par,
+                                )
+                                .into();
+                            Ok(arr.into())
+                        },
+                    )
+                }
+            }),
+        };
+        self.chain(node)
+    }
 }
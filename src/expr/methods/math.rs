@@ -0,0 +1,317 @@
+use crate::prelude::*;
+use tevec::ndarray::Zip;
+
+/// generate elementwise expressions, the data is mapped lazily if it can be
+/// turned into an iterator, otherwise it is mapped as an array
+macro_rules! impl_expr_map {
+    ($($(#[$meta: meta])* $func: ident($($arg: ident: $ty: ty),*)),* $(,)?) => {
+        impl Expr {
+            $(
+                $(#[$meta])*
+                pub fn $func(self, $($arg: $ty),*) -> Self {
+                    let node = BaseNode {
+                        name: stringify!($func),
+                        elementwise: true,
+                        func: Arc::new(move |data, backend| match data.try_into_iter() {
+                            Ok(iter) => Ok(iter.$func($($arg),*)?.into()),
+                            Err(data) => {
+                                if let Ok(iter) = data.try_titer() {
+                                    return iter.$func($($arg),*)?.collect(backend);
+                                }
+                                let arr: DynArray = data.into_array()?.$func($($arg),*)?;
+                                Ok(arr.into())
+                            }
+                        }),
+                    };
+                    self.chain(node)
+                }
+            )*
+        }
+    };
+}
+
+impl_expr_map!(
+    sqrt(),
+    vsqrt(),
+    cbrt(),
+    vcbrt(),
+    exp(),
+    vexp(),
+    log1p(),
+    vlog1p(),
+    sin(),
+    vsin(),
+    cos(),
+    vcos(),
+    tan(),
+    vtan(),
+    asin(),
+    vasin(),
+    acos(),
+    vacos(),
+    atan(),
+    vatan(),
+    sinh(),
+    vsinh(),
+    cosh(),
+    vcosh(),
+    tanh(),
+    vtanh(),
+    /// Logarithm with respect to `base`, natural logarithm if `base` is none
+    log(base: Option<f64>),
+    vlog(base: Option<f64>),
+    sign(),
+    vsign(),
+    floor(),
+    vfloor(),
+    ceil(),
+    vceil(),
+    /// Round the values to `decimals` decimal places, integers are kept
+    round(decimals: u32),
+    vround(decimals: u32),
+);
+
+impl Expr {
+    fn pow_node(self, exponent: Expr, nan_aware: bool) -> Self {
+        let node = CtxNode {
+            name: if nan_aware { "vpow" } else { "pow" },
+            func: Arc::new(move |data, ctx, backend| {
+                let exponent = match exponent.eval(ctx, None)?.try_into_scalar() {
+                    Ok(exponent) => exponent,
+                    Err(exponent) => return pow_elementwise(data, exponent, nan_aware, backend),
+                };
+                match data.try_into_iter() {
+                    Ok(iter) => Ok(pow_iter(iter, exponent, nan_aware)?.into()),
+                    Err(data) => {
+                        if let Ok(iter) = data.try_titer() {
+                            return pow_iter(iter, exponent, nan_aware)?.collect(backend);
+                        }
+                        let arr = data.into_array()?;
+                        let arr: DynArray = if nan_aware {
+                            arr.vpow(exponent)?
+                        } else {
+                            arr.pow(exponent)?
+                        };
+                        Ok(arr.into())
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Raise the values to the power of `exponent`. For a scalar exponent,
+    /// integers are kept if it is a non-negative integer, otherwise they are
+    /// promoted to float. The exponent can also have the same length or shape
+    /// as the data, then the values are raised elementwise as float.
+    #[inline]
+    pub fn pow(self, exponent: Expr) -> Self {
+        self.pow_node(exponent, false)
+    }
+
+    /// Like [`Expr::pow`], but none values are kept as none
+    #[inline]
+    pub fn vpow(self, exponent: Expr) -> Self {
+        self.pow_node(exponent, true)
+    }
+
+    /// Limit the values to `[lower, upper]`, the bound is ignored if it is none
+    pub fn clip(self, lower: Option<Expr>, upper: Option<Expr>) -> Self {
+        let node = CtxNode {
+            name: "clip",
+            func: Arc::new(move |data, ctx, backend| {
+                let (lower, upper) = eval_bounds(&lower, &upper, ctx)?;
+                match data.try_into_iter() {
+                    Ok(iter) => Ok(iter.clip(lower, upper)?.into()),
+                    Err(data) => {
+                        if let Ok(iter) = data.try_titer() {
+                            return iter.clip(lower, upper)?.collect(backend);
+                        }
+                        let arr: DynArray = data.into_array()?.clip(lower, upper)?;
+                        Ok(arr.into())
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    pub fn vclip(self, lower: Option<Expr>, upper: Option<Expr>) -> Self {
+        let node = CtxNode {
+            name: "vclip",
+            func: Arc::new(move |data, ctx, backend| {
+                let (lower, upper) = eval_bounds(&lower, &upper, ctx)?;
+                match data.try_into_iter() {
+                    Ok(iter) => Ok(iter.vclip(lower, upper)?.into()),
+                    Err(data) => {
+                        if let Ok(iter) = data.try_titer() {
+                            return iter.vclip(lower, upper)?.collect(backend);
+                        }
+                        let arr: DynArray = data.into_array()?.vclip(lower, upper)?;
+                        Ok(arr.into())
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+}
+
+#[inline]
+fn pow_iter(iter: DynTrustIter, exponent: Scalar, nan_aware: bool) -> TResult<DynTrustIter> {
+    if nan_aware {
+        iter.vpow(exponent)
+    } else {
+        iter.pow(exponent)
+    }
+}
+
+/// raise the values to the power of the exponents elementwise, the result is float
+fn pow_elementwise<'a>(
+    data: Data<'a>,
+    exponent: Data<'_>,
+    nan_aware: bool,
+    backend: Backend,
+) -> TResult<Data<'a>> {
+    let pow = move |x: f64, y: f64| {
+        if nan_aware && x.is_nan() {
+            f64::NAN
+        } else {
+            x.powf(y)
+        }
+    };
+    match data {
+        Data::Array(ref arr) if arr.ndim() > 1 => {
+            let x = arr.to_f64_array()?;
+            let y = exponent.into_array()?.to_f64_array()?;
+            tensure!(
+                x.shape() == y.shape(),
+                "the shape of the exponent should be the same as the data, got {:?} and {:?}",
+                y.shape(),
+                x.shape()
+            );
+            let out: DynArray = Zip::from(&x).and(&y).map_collect(|x, y| pow(*x, *y)).into();
+            Ok(out.into())
+        }
+        data => {
+            let x = data.collect_f64()?;
+            let y = exponent.collect_f64()?;
+            tensure!(x.len() == y.len(), lm, x.len(), y.len());
+            let out: DynVec = x
+                .into_iter()
+                .zip(y)
+                .map(|(x, y)| pow(x, y))
+                .collect::<Vec<_>>()
+                .into();
+            out.into_backend(backend)
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn eval_bounds(
+    lower: &Option<Expr>,
+    upper: &Option<Expr>,
+    ctx: &Context,
+) -> TResult<(Option<f64>, Option<f64>)> {
+    let eval = |e: &Option<Expr>| -> TResult<Option<f64>> {
+        match e {
+            Some(e) => Ok(Some(e.eval(ctx, None)?.into_scalar()?.cast_f64()?)),
+            None => Ok(None),
+        }
+    };
+    Ok((eval(lower)?, eval(upper)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{d2_array, d_vec};
+    use tevec::core::testing::assert_vec1d_equal_numeric;
+
+    #[test]
+    fn test_math() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![4, -9, 0, 16].into(),
+                d_vec![1.234, f64::NAN, -2.5, 0.0].into(),
+            ],
+            ["a", "b"],
+        );
+        // integers are promoted to float
+        let res = s("a").abs().sqrt().eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.f64()?.as_ref(), &[2.0, 3.0, 0.0, 4.0]);
+        let res = s("a").sign().eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[1, -1, 0, 1]);
+        let res = s("a").pow(lit(2)).eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[16, 81, 0, 256]);
+        let res = s("a").abs().pow(lit(0.5)).eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.f64()?.as_ref(), &[2.0, 3.0, 0.0, 4.0]);
+        let res = s("a")
+            .clip(Some(lit(0)), Some(lit(10)))
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[4, 0, 0, 10]);
+        // the bounds are rounded inwards for integers
+        let res = s("a")
+            .clip(Some(lit(-8.5)), Some(lit(3.5)))
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[3, -8, 0, 3]);
+        // integer overflow is an error rather than wrapping
+        assert!(s("a").pow(lit(20)).eval(&ctx, None).is_err());
+        assert!(s("a").vpow(lit(20)).eval(&ctx, None).is_err());
+        // raise to the power of an expression elementwise
+        let res = s("a")
+            .abs()
+            .pow(s("b").abs().floor())
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_vec1d_equal_numeric(
+            &res.into_vec()?.f64()?.to_vec(),
+            &[4., f64::NAN, 0., 1.],
+            None,
+        );
+        let res = s("b").vpow(s("a").abs()).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?.to_vec();
+        assert!(res[1].is_nan() && res[3] == 0.);
+        assert!(s("a").pow(s("b").head(2)).eval(&ctx, None).is_err());
+        let res = s("a").abs().log(Some(2.)).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?;
+        assert_vec1d_equal_numeric(&res[..2].to_vec(), &[2.0, 9f64.log2()], None);
+        // nan aware functions
+        let res = s("b").vround(1).eval(&ctx, Some(Backend::Vec))?;
+        assert_vec1d_equal_numeric(
+            &res.into_vec()?.f64()?.to_vec(),
+            &[1.2, f64::NAN, -2.5, 0.0],
+            None,
+        );
+        let res = s("b").vfloor().eval(&ctx, Some(Backend::Vec))?;
+        assert_vec1d_equal_numeric(
+            &res.into_vec()?.f64()?.to_vec(),
+            &[1.0, f64::NAN, -3.0, 0.0],
+            None,
+        );
+        let res = s("b").vsign().eval(&ctx, Some(Backend::Vec))?;
+        assert_vec1d_equal_numeric(
+            &res.into_vec()?.f64()?.to_vec(),
+            &[1.0, f64::NAN, -1.0, 0.0],
+            None,
+        );
+        let res = s("b").vexp().vlog(None).eval(&ctx, Some(Backend::Vec))?;
+        assert_vec1d_equal_numeric(
+            &res.into_vec()?.f64()?.to_vec(),
+            &[1.234, f64::NAN, -2.5, 0.0],
+            None,
+        );
+        // map on array
+        let ctx = Context::new(d2_array![[1, -2], [3, -4]]);
+        let res = s(0)
+            .vclip(None, Some(lit(2)))
+            .eval(&ctx, None)?
+            .into_array()?;
+        assert_eq!(res.i32()?.view().as_slice().unwrap(), &[1, -2, 2, -4]);
+        let res = s(0).vsin().eval(&ctx, None)?.into_array()?;
+        assert_eq!(res.f64()?.view().as_slice().unwrap()[0], 1f64.sin());
+        let res = s(0).pow(s(0).abs()).eval(&ctx, None)?.into_array()?;
+        assert_eq!(res.f64()?.view().as_slice().unwrap(), &[1., 4., 27., 256.]);
+        Ok(())
+    }
+}
@@ -1,6 +1,44 @@
 // #[cfg(feature = "agg")]
 mod agg;
+#[cfg(feature = "ndarray")]
+mod concat;
+#[cfg(feature = "map")]
+mod cross_section;
+#[cfg(feature = "rolling")]
+mod ewm;
+#[cfg(feature = "map")]
+mod group_by;
 #[cfg(feature = "map")]
 mod map;
+#[cfg(feature = "map")]
+mod math;
 #[cfg(feature = "rolling")]
 mod rolling;
+#[cfg(feature = "map")]
+mod select;
+#[cfg(feature = "map")]
+mod sort;
+#[cfg(feature = "map")]
+mod unique;
+
+#[cfg(test)]
+use crate::prelude::*;
+#[cfg(all(test, feature = "map"))]
+use tevec::ndarray::Array2;
+
+/// evaluate the expression and collect the result as float, shared by the tests
+#[cfg(test)]
+fn eval_f64(expr: Expr, ctx: &Context) -> TResult<Vec<f64>> {
+    Ok(expr
+        .eval(ctx, Some(Backend::Vec))?
+        .into_vec()?
+        .f64()?
+        .to_vec())
+}
+
+/// evaluate the expression and collect the result as a 2-D float array
+#[cfg(all(test, feature = "map"))]
+fn eval_f64_2d(expr: Expr, ctx: &Context) -> TResult<Array2<f64>> {
+    let res = expr.eval(ctx, None)?.into_array()?.f64()?;
+    Ok(res.view().into_dimensionality().unwrap().to_owned())
+}
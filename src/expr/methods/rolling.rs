@@ -1,128 +1,87 @@
+use super::concat::{concat, concat_iter};
 use crate::prelude::*;
-use tevec::ndarray::{concatenate, ArrayView1, Axis, Ix1};
+use tevec::ndarray::parallel::prelude::*;
+use tevec::ndarray::{Array, ArrayD, ArrayView1, Axis, Ix1, IxDyn, Slice};
 #[cfg(feature = "pl")]
 use tevec::polars::prelude::Series;
 
 #[derive(Clone)]
-pub struct Rolling {
-    window: usize,
-    expr: Expr,
+enum RollingWindow {
+    /// window of a fixed number of rows
+    Fixed(usize),
+    /// window grows from the start of the data
+    Expanding,
+    /// window of a time period, the rows in the window are decided by the time of each row
+    #[cfg(feature = "time")]
+    Time {
+        by: Expr,
+        period: TimeDelta,
+        closed: ClosedWindow,
+    },
 }
 
-const INIT_VEC_LENGTH: usize = 100;
+/// Weights of a weighted rolling window, ordered from the oldest row
+/// to the latest row of the window
+#[derive(Clone)]
+pub enum WindowWeights {
+    /// weights evaluated once from the context
+    Expr(Expr),
+    /// weights generated by the window size
+    Kernel(WeightKernel),
+}
 
-#[allow(clippy::clone_on_copy)]
-pub fn concat_iter<'a, 'b, I: IntoIterator<Item = DynTrustIter<'a>>>(
-    is: I,
-    size_hint: Option<usize>,
-) -> TResult<DynVec<'b>> {
-    let mut iter = is.into_iter();
-    if let Some(i1) = iter.next() {
-        match_trust_iter!(i1; Cast(i1) => {
-            let iter_len = i1.len();
-            let mut vec = if let Some(size) = size_hint {
-                Vec::with_capacity(size * iter_len)
-            } else {
-                Vec::with_capacity(iter_len * INIT_VEC_LENGTH)
-            };
-            vec.extend(i1);
-            // a type hint iter so that we don't need to write code for each case
-            // TODO(Teamon): is there a better way to do auto type cast?
-            let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
-            for i in iter {
-                match_trust_iter!(i; Cast(i) => {
-                    vec.extend(i.cast_with(&type_hint));
-                    Ok(())
-                },)?;
-            }
-            Ok(vec.into())
-        },)
-    } else {
-        Ok(Default::default())
+impl From<Expr> for WindowWeights {
+    #[inline]
+    fn from(expr: Expr) -> Self {
+        WindowWeights::Expr(expr)
     }
 }
 
-#[allow(clippy::clone_on_copy)]
-pub fn concat(es: Vec<Data>, axis: Option<usize>, backend: Backend) -> TResult<Data> {
-    let es_len = es.len();
-    if es.is_empty() {
-        return Ok(Default::default());
-    } else if es_len == 1 {
-        return Ok(es.into_iter().next().unwrap());
-    }
-    // fast path for concat iter
-    if Vec::get(&es, 0).unwrap().is_trust_iter() && Vec::get(&es, 1).unwrap().is_trust_iter() {
-        let is = es
-            .into_iter()
-            .map(|e| e.try_into_iter().unwrap())
-            .collect_trusted_to_vec();
-        return concat_iter(is, Some(es_len)).map(move |v| v.into_backend(backend).unwrap());
+impl From<WeightKernel> for WindowWeights {
+    #[inline]
+    fn from(kernel: WeightKernel) -> Self {
+        WindowWeights::Kernel(kernel)
     }
+}
 
-    #[cfg(feature = "pl")]
-    if Vec::get(&es, 0).unwrap().is_series() {
-        // this is needed because we can't consume polars series and turn it into a trust iter
-        let s1 = Vec::get(&es, 0).unwrap();
-        let i1 = s1.try_titer().unwrap();
-        let out: Data<'_> = match_trust_iter!(i1; Cast(i1) => {
-            let iter_len = i1.len();
-            let mut vec = Vec::with_capacity(iter_len * es_len);
-            vec.extend(i1);
-            let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
-            for i in es.iter() {
-                match_trust_iter!(i.try_titer()?; Cast(i) => {
-                    // a type hint iter so that we don't need to write code for each case
-                    vec.extend(i.cast_with(&type_hint));
-                    Ok(())
-                },)?;
-            }
-            Ok(vec.into())
-        },)?;
-        return Ok(out.into_series().unwrap().into());
-    }
-    let len = es.len();
-
-    let mut es = es.into_iter();
-    let d1 = es.next().unwrap(); // first data
-    match d1.try_into_iter() {
-        Ok(i1) => {
-            let out: TResult<DynVec<'_>> = match_trust_iter!(i1; Cast(i1) => {
-                let iter_len = i1.len();
-                let mut vec = Vec::with_capacity(iter_len * es_len);
-                vec.extend(i1);
-                let type_hint = std::iter::once(Vec::get(&vec, 0).unwrap().clone());
-                for i in es {
-                    match_trust_iter!(i.try_into_iter().unwrap(); Cast(i) => {
-                        vec.extend(i.cast_with(&type_hint));
-                        Ok(())
-                    },)?;
-                }
-                Ok(vec.into())
-            },);
-            out.map(|v| v.into_backend(backend).unwrap())
-        }
-        Err(d1) => {
-            // multi dimensional array
-            match_array!(d1.into_array()?; Cast(a1) => {
-                // cast data to the same dtype
-                let mut data = Vec::with_capacity(len);
-                data.push(a1);
-                es.for_each(|e| {
-                    match_array!(e.into_array().unwrap(); Cast(a) => {
-                        data.push(a.cast_into());
-                        Ok(())
-                    },).unwrap();
-                });
-                // create a view of the data
-                let arr_views = data.iter().map(|a| a.view()).collect_trusted_to_vec();
-                let out = concatenate(Axis(axis.unwrap_or(0)), &arr_views).map_err(|_| terr!("concat array error"))?;
-                let out: DynArray = out.into();
-                Ok(out.into())
-            },)
-        }
+impl WindowWeights {
+    fn eval(&self, window: usize, ctx: &Context) -> TResult<Vec<f64>> {
+        let weights = match self {
+            WindowWeights::Expr(expr) => expr.eval(ctx, None)?.collect_f64()?,
+            WindowWeights::Kernel(kernel) => kernel.weights(window)?,
+        };
+        tensure!(
+            weights.len() == window,
+            "the length of weights should be equal to window {}, got {}",
+            window,
+            weights.len()
+        );
+        tensure!(
+            Iterator::all(&mut weights.iter(), |w| w.is_finite()),
+            "weights should not be none or infinite"
+        );
+        Ok(weights)
     }
 }
 
+#[derive(Clone)]
+pub struct Rolling {
+    window: RollingWindow,
+    /// the closed sides set by [`Rolling::closed`], only valid for time window
+    #[cfg(feature = "time")]
+    closed: Option<ClosedWindow>,
+    min_periods: Option<usize>,
+    center: bool,
+    axis: Option<usize>,
+    par: Option<bool>,
+    step: usize,
+    ffill: bool,
+    expr: Expr,
+}
+
+/// the windows are split into more chunks than threads to balance the load
+const PAR_CHUNKS_PER_THREAD: usize = 4;
+
 #[allow(unreachable_patterns)]
 fn vec_rolling<'b>(
     vec: Arc<DynVec<'b>>,
@@ -178,6 +137,8 @@ fn array_rolling<'b>(
     )
 }
 
+/// each window is a slice of the series which shares its memory, the result
+/// of each window is copied out by `into_owned`
 #[cfg(feature = "pl")]
 fn series_rolling<'b>(
     se: Series,
@@ -185,60 +146,1090 @@ fn series_rolling<'b>(
     func: &Expr,
     backend: Backend,
 ) -> TResult<Data<'b>> {
-    match_series!(
-        se,
-        s;
-        {
-            let iter = s.rolling_custom_iter(window, move |v| {
-                let series: Series = if let std::borrow::Cow::Owned(s) = v {
-                    s.into()
-                } else{
-                    unreachable!("implement error")
-                };
-                let ctx = Context::new(series);
-                let res = func.eval(&ctx, Some(backend)).unwrap();
-                res.into_owned(Some(backend)).unwrap()
-            }).collect_trusted_to_vec();
-            let res = concat(iter, None, backend)?;
-            Ok(res)
+    let func = func.to_func();
+    let out = (0..se.len())
+        .map(|i| {
+            let start = (i + 1).saturating_sub(window);
+            let ctx = Context::new(se.slice(start as i64, i + 1 - start));
+            let res = func(&ctx, Some(backend))?.into_result(Some(backend))?;
+            res.into_owned(Some(backend))
+                .map_err(|_| terr!("rolling apply result can not be owned"))
+        })
+        .collect::<TResult<Vec<_>>>()?;
+    concat(out, None, backend)
+}
+
+/// check whether the expression selects the data of the window, maps it elementwise
+/// and then aggregates it, e.g. `s(0).abs().sum()`. Such expression can be
+/// calculated incrementally. Only `sum` is recognised as the aggregation and
+/// only the nodes flagged as elementwise as the maps.
+fn is_incremental(func: &Expr) -> bool {
+    let Some((select, rest)) = func.nodes.split_first() else {
+        return false;
+    };
+    let Some((agg, maps)) = rest.split_last() else {
+        return false;
+    };
+    matches!(select, Node::Select(n) if matches!(n.symbol, Symbol::I32(0 | -1) | Symbol::Usize(0)))
+        && matches!(agg, Node::Base(n) if n.name == "sum")
+        && Iterator::all(
+            &mut maps.iter(),
+            |node| matches!(node, Node::Base(n) if n.elementwise),
+        )
+}
+
+/// apply the elementwise nodes of an incremental expression to the whole data
+/// and then calculate the rolling sum in O(n)
+fn fast_rolling<'b>(
+    data: Data<'b>,
+    window: usize,
+    func: &Expr,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    let mut data = data;
+    for node in &func.nodes[1..func.nodes.len() - 1] {
+        if let Node::Base(n) = node {
+            data = (n.func)(data, backend)?;
         }
-    )
+    }
+    let out = match data.try_into_iter() {
+        Ok(iter) => iter.rolling_vsum(window)?,
+        Err(data) => data.try_titer()?.rolling_vsum(window)?,
+    };
+    out.into_backend(backend)
+}
+
+/// evaluate the expression on each trailing window of the data
+fn rolling_apply_data<'b>(
+    data: Data<'b>,
+    window: usize,
+    func: &Expr,
+    incremental: bool,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    if incremental && !matches!(&data, Data::Array(arr) if arr.ndim() != 1) {
+        return fast_rolling(data, window, func, backend);
+    }
+    match data {
+        Data::Vec(vec) => vec_rolling(vec, window, func, backend),
+        Data::TrustIter(iter) => match Arc::try_unwrap(iter) {
+            Ok(iter) => vec_rolling(iter.collect_vec()?.into(), window, func, backend),
+            Err(_) => {
+                tbail!("trust iter is shared, cann't collect and rolling shared iter")
+            }
+        },
+        Data::Array(arr) => array_rolling(arr, window, func, backend),
+        Data::Scalar(_) => tbail!("rolling apply not supported for scalar"),
+        #[cfg(feature = "pl")]
+        Data::Series(series) => series_rolling(series, window, func, backend),
+    }
+}
+
+/// drop the results of the padded windows and set the results of windows
+/// without enough valid values to none
+fn rolling_apply_post<'b>(
+    iter: DynTrustIter<'_>,
+    offset: usize,
+    mask: Option<Vec<bool>>,
+) -> TResult<DynVec<'b>> {
+    let iter = match mask {
+        Some(mask) => {
+            tensure!(
+                mask.len() == iter.len(),
+                "rolling apply should return a scalar for each window when min_periods is set"
+            );
+            iter.mask_none(mask)?
+        }
+        None => iter,
+    };
+    iter.skip(offset).collect_vec()
+}
+
+/// evaluate `f` on each lane along `axis` of a multi dimensional array, the lanes
+/// are evaluated in parallel if `par` is true. The result of each lane should have
+/// the same length and they are assembled with `axis` as the rolling axis.
+#[allow(unreachable_patterns)]
+fn lanes_rolling<'b, F>(
+    arr: &DynArray<'_>,
+    axis: Option<usize>,
+    par: Option<bool>,
+    f: F,
+) -> TResult<Data<'b>>
+where
+    F: Fn(Data<'_>) -> TResult<DynVec<'static>> + Send + Sync,
+{
+    let axis = axis.unwrap_or(0);
+    let ndim = arr.ndim();
+    tensure!(
+        axis < ndim,
+        "axis {} is out of bounds for array of dimension {}",
+        axis,
+        ndim
+    );
+    let lanes: ArrayD<TResult<DynVec<'static>>> = match_array!(arr; Dynamic(a) => {
+        Ok(a.view().calc_agg_func(
+            |lane| {
+                let lane: DynArray = lane.into_dyn().into();
+                f(lane.into())
+            },
+            Some(axis),
+            par,
+        ))
+    },)?;
+    // the lanes are in logical order of the other axes
+    let mut shape = lanes.shape().to_vec();
+    let lanes = lanes.into_iter().collect::<TResult<Vec<_>>>()?;
+    let lane_len = <[DynVec]>::first(&lanes).map_or(0, |v| v.len());
+    tensure!(
+        Iterator::all(&mut lanes.iter(), |v| v.len() == lane_len),
+        "rolling apply should return the same length for each lane"
+    );
+    shape.push(lane_len);
+    // move the rolling axis from the last to `axis`
+    let mut perm: Vec<usize> = (0..ndim - 1).collect();
+    perm.insert(axis, ndim - 1);
+    let n_lanes = lanes.len();
+    let iters = lanes
+        .into_iter()
+        .map(|v| v.into_titer())
+        .collect::<TResult<Vec<_>>>()?;
+    let out = concat_iter(iters, Some(n_lanes))?;
+    match_vec!(out; Dynamic(v) => {
+        let arr = Array::from_shape_vec(IxDyn(&shape), v.into_owned())
+            .map_err(|e| terr!("{}", e))?
+            .permuted_axes(IxDyn(&perm));
+        let arr: DynArray = arr.into();
+        Ok(arr.into())
+    },)
+}
+
+/// get the window of a fixed window, an expanding window is the same as
+/// a fixed window whose length is the length of the data along the axis
+fn resolve_window(
+    window: Option<usize>,
+    data: &Data,
+    center: bool,
+    axis: Option<usize>,
+) -> TResult<usize> {
+    if let Some(window) = window {
+        return Ok(window);
+    }
+    tensure!(!center, "center is not supported for expanding window");
+    let len = match data {
+        Data::Array(arr) if arr.ndim() > 1 => {
+            let (shape, axis) = (arr.shape(), axis.unwrap_or(0));
+            tensure!(axis < shape.len(), "axis {} is out of bounds", axis);
+            shape[axis]
+        }
+        Data::Array(arr) => arr.len(),
+        Data::Vec(vec) => vec.len(),
+        Data::TrustIter(iter) => iter.len(),
+        Data::Scalar(_) => 1,
+        #[cfg(feature = "pl")]
+        Data::Series(s) => s.len(),
+    };
+    Ok(len.max(1))
+}
+
+/// evaluate the expression on each fixed window, the result is labeled at the
+/// center of the window if `center` is true
+#[allow(clippy::too_many_arguments)]
+fn fixed_rolling_apply<'b>(
+    data: Data<'b>,
+    window: usize,
+    min_periods: Option<usize>,
+    center: bool,
+    func: &Expr,
+    incremental: bool,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    let offset = center_offset(window, center);
+    if offset == 0 && min_periods.is_none() {
+        return rolling_apply_data(data, window, func, incremental, backend);
+    }
+    let data: Data = if offset > 0 {
+        data.with_titer(|iter| iter.pad_none(offset)?.collect_vec())?
+            .into()
+    } else if matches!(data, Data::TrustIter(_)) {
+        // the iterator is consumed by the valid count, so it should be collected
+        data.with_titer(|iter| iter.collect_vec())?.into()
+    } else {
+        data
+    };
+    let mask = match min_periods {
+        Some(mp) => {
+            let count = data.try_titer()?.rolling_valid_count(window)?;
+            Some(count.into_iter().map(|n| n < mp).collect())
+        }
+        None => None,
+    };
+    let out = rolling_apply_data(data, window, func, incremental, backend)?;
+    let out = match out.try_into_iter() {
+        Ok(iter) => rolling_apply_post(iter, offset, mask)?,
+        Err(out) => rolling_apply_post(out.try_titer()?, offset, mask)?,
+    };
+    out.into_backend(backend)
+}
+
+/// evaluate the expression on each window `[start, end)` of the aligned columns,
+/// the context of each window has a column map if `names` is given.
+///
+/// If `par` is true, the windows are split into chunks and each chunk is
+/// evaluated on a thread, the results are concatenated in order.
+fn bounds_rolling_apply<'b>(
+    cols: &[DynVec<'_>],
+    names: Option<&[Arc<str>]>,
+    bounds: &[(usize, usize)],
+    min_periods: Option<usize>,
+    func: &Expr,
+    par: bool,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    let len = cols.first().map_or(0, |col| col.len());
+    for col in cols {
+        tensure!(col.len() == len, lm, col.len(), len);
+    }
+    let func = func.to_func();
+    let eval_window = |&(start, end): &(usize, usize)| -> TResult<Data<'b>> {
+        let ctx = match names {
+            Some(names) => {
+                let data = cols
+                    .iter()
+                    .map(|col| Ok(col.window_view(start, end)?.into()))
+                    .collect::<TResult<Vec<Data>>>()?;
+                Context::new_from_data_column(data, names.iter().cloned())
+            }
+            None => {
+                tensure!(
+                    cols.len() == 1,
+                    "column names are needed for multiple columns"
+                );
+                Context::new(cols[0].window_view(start, end)?)
+            }
+        };
+        let res = func(&ctx, Some(backend))?.into_result(Some(backend))?;
+        res.into_owned(Some(backend))
+            .map_err(|_| terr!("rolling apply result can not be owned"))
+    };
+    let out = if par && bounds.len() > 1 {
+        let n_chunks = rayon::current_num_threads() * PAR_CHUNKS_PER_THREAD;
+        let chunk_size = bounds.len().div_ceil(n_chunks);
+        let chunks = (0..bounds.len().div_ceil(chunk_size))
+            .into_par_iter()
+            .map(|i| {
+                let chunk = &bounds[i * chunk_size..((i + 1) * chunk_size).min(bounds.len())];
+                chunk.iter().map(eval_window).collect::<TResult<Vec<_>>>()
+            })
+            .collect::<TResult<Vec<_>>>()?;
+        chunks.into_iter().flatten().collect()
+    } else {
+        bounds
+            .iter()
+            .map(eval_window)
+            .collect::<TResult<Vec<_>>>()?
+    };
+    let out = concat(out, None, backend)?;
+    let mask = match min_periods {
+        Some(mp) => {
+            // a row is valid only if the values of all columns are valid
+            let mut valid = vec![true; len];
+            for col in cols {
+                let col_valid = col.titer()?.valid_mask()?;
+                valid.iter_mut().zip(col_valid).for_each(|(v, c)| *v &= c);
+            }
+            let count = bounds_valid_count(&valid, bounds);
+            Some(count.into_iter().map(|n| n < mp).collect())
+        }
+        None => return Ok(out),
+    };
+    let out = match out.try_into_iter() {
+        Ok(iter) => rolling_apply_post(iter, 0, mask)?,
+        Err(out) => rolling_apply_post(out.try_titer()?, 0, mask)?,
+    };
+    out.into_backend(backend)
+}
+
+/// evaluate the expression on the time window `[start, end)` of every `step` rows
+#[cfg(feature = "time")]
+fn time_rolling_apply<'b>(
+    data: Data<'_>,
+    bounds: &[(usize, usize)],
+    min_periods: Option<usize>,
+    (step, ffill, par): (usize, bool, bool),
+    func: &Expr,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    let vec = data.with_titer(|iter| iter.collect_vec())?;
+    let len = vec.len();
+    tensure!(len == bounds.len(), lm, len, bounds.len());
+    let bounds = step_bounds(bounds.to_vec(), step);
+    let out = bounds_rolling_apply(&[vec], None, &bounds, min_periods, func, par, backend)?;
+    broadcast_strided(out, step, ffill, len, backend)
+}
+
+/// keep the bounds of every `step` rows
+#[inline]
+fn step_bounds(bounds: Vec<(usize, usize)>, step: usize) -> Vec<(usize, usize)> {
+    if step == 1 {
+        bounds
+    } else {
+        bounds.into_iter().step_by(step).collect()
+    }
+}
+
+/// collect the result after a lazy transformation
+fn map_result<'b, F>(out: Data<'_>, backend: Backend, f: F) -> TResult<Data<'b>>
+where
+    F: for<'c> FnOnce(DynTrustIter<'c>) -> TResult<DynTrustIter<'c>>,
+{
+    let out = match out.try_into_iter() {
+        Ok(iter) => f(iter)?.collect_vec()?,
+        Err(out) => f(out.try_titer()?)?.collect_vec()?,
+    };
+    out.into_backend(backend)
+}
+
+/// keep the values of every `step` rows of a result which has a value for each row,
+/// the values of the other rows are forward filled instead if `ffill` is true
+fn stride_full<'b>(out: Data<'b>, step: usize, ffill: bool, backend: Backend) -> TResult<Data<'b>> {
+    match (step, ffill) {
+        (1, _) => Ok(out),
+        (_, true) => map_result(out, backend, |iter| iter.step_ffill(step)),
+        (_, false) => map_result(out, backend, |iter| iter.step_by(step)),
+    }
+}
+
+/// forward fill the result of every `step` rows back to the full length `len`
+/// if `ffill` is true
+fn broadcast_strided<'b>(
+    out: Data<'b>,
+    step: usize,
+    ffill: bool,
+    len: usize,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    if step == 1 || !ffill {
+        return Ok(out);
+    }
+    map_result(out, backend, |iter| iter.repeat_each(step, len))
+}
+
+/// the same as [`stride_full`] for each lane along `axis`
+fn stride_array(arr: ArrayD<f64>, axis: Option<usize>, step: usize, ffill: bool) -> ArrayD<f64> {
+    let axis = Axis(axis.unwrap_or(0));
+    match (step, ffill) {
+        (1, _) => arr,
+        (_, true) => {
+            let mut arr = arr;
+            arr.lanes_mut(axis).into_iter().for_each(|mut lane| {
+                for i in 0..lane.len() {
+                    lane[i] = lane[i - i % step];
+                }
+            });
+            arr
+        }
+        (_, false) => arr
+            .slice_axis(axis, Slice::new(0, None, step as isize))
+            .to_owned(),
+    }
+}
+
+/// evaluate the expression on the fixed window of every `step` rows, an
+/// incremental expression is calculated on each row and then strided.
+/// The windows are evaluated in parallel chunks if `par` is true
+#[allow(clippy::too_many_arguments)]
+fn strided_rolling_apply<'b>(
+    data: Data<'b>,
+    window: usize,
+    min_periods: Option<usize>,
+    center: bool,
+    (step, ffill, par): (usize, bool, bool),
+    func: &Expr,
+    incremental: bool,
+    backend: Backend,
+) -> TResult<Data<'b>> {
+    if (step == 1 && !par) || incremental {
+        let out = fixed_rolling_apply(
+            data,
+            window,
+            min_periods,
+            center,
+            func,
+            incremental,
+            backend,
+        )?;
+        return stride_full(out, step, ffill, backend);
+    }
+    let vec = match data.try_into_iter() {
+        Ok(iter) => iter.collect_vec()?,
+        Err(data) => data.try_titer()?.collect_vec()?,
+    };
+    let len = vec.len();
+    let bounds = step_bounds(fixed_window_bounds(len, window, center), step);
+    let out = bounds_rolling_apply(&[vec], None, &bounds, min_periods, func, par, backend)?;
+    broadcast_strided(out, step, ffill, len, backend)
+}
+
+impl RollingWindow {
+    /// get the bounds `[start, end)` of the window of each row
+    #[allow(unused_variables)]
+    fn bounds(&self, len: usize, center: bool, ctx: &Context) -> TResult<Vec<(usize, usize)>> {
+        match self {
+            RollingWindow::Fixed(window) => {
+                tensure!(*window > 0, "window should be greater than 0");
+                Ok(fixed_window_bounds(len, *window, center))
+            }
+            RollingWindow::Expanding => {
+                tensure!(!center, "center is not supported for expanding window");
+                Ok(fixed_window_bounds(len, len.max(1), false))
+            }
+            #[cfg(feature = "time")]
+            RollingWindow::Time { by, period, closed } => {
+                tensure!(!center, "center is not supported for time window");
+                let bounds = eval_time_bounds(by, *period, *closed, ctx)?;
+                tensure!(bounds.len() == len, lm, bounds.len(), len);
+                Ok(bounds)
+            }
+        }
+    }
 }
 
 impl Rolling {
-    pub fn apply(self, func: Expr) -> Expr {
+    /// Minimum number of valid values in a window required to have a value,
+    /// otherwise the result is none. It defaults to the window length for
+    /// [`Rolling::agg`] and [`Rolling::order_agg`] on a fixed window and 1
+    /// otherwise, the pair aggregations need at least 2 valid pairs.
+    #[inline]
+    pub fn min_periods(mut self, min_periods: usize) -> Self {
+        self.min_periods = Some(min_periods);
+        self
+    }
+
+    /// Set the labels at the center of the window, for even window
+    /// the center is the right one of the two middle values
+    #[inline]
+    pub fn center(mut self, center: bool) -> Self {
+        self.center = center;
+        self
+    }
+
+    /// The axis to roll along for multi dimensional arrays, each lane along
+    /// the axis is calculated independently, defaults to 0
+    #[inline]
+    pub fn axis(mut self, axis: usize) -> Self {
+        self.axis = Some(axis);
+        self
+    }
+
+    /// Whether to calculate the lanes of a multi dimensional array in parallel,
+    /// for 1-D data the windows of a custom expression are split into chunks
+    /// and evaluated in parallel
+    #[inline]
+    pub fn par(mut self, par: bool) -> Self {
+        self.par = Some(par);
+        self
+    }
+
+    /// Only evaluate the window of every `step` rows starting from the first row,
+    /// the result is shorter than the data unless `ffill` is true
+    #[inline]
+    pub fn step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Broadcast the result of a strided rolling back to the full length, the
+    /// rows between the evaluated rows are forward filled
+    #[inline]
+    pub fn ffill(mut self, ffill: bool) -> Self {
+        self.ffill = ffill;
+        self
+    }
+
+    /// Which sides of the window are closed, only supported by time window,
+    /// the rolling expression fails to evaluate if it is set on other windows
+    #[cfg(feature = "time")]
+    #[inline]
+    pub fn closed(mut self, closed: ClosedWindow) -> Self {
+        self.closed = Some(closed);
+        self
+    }
+
+    /// move the closed sides set by [`Rolling::closed`] into the time window,
+    /// returns whether they are set on a window other than a time window
+    fn resolve_closed(&mut self) -> bool {
+        #[cfg(feature = "time")]
+        if let Some(closed) = self.closed.take() {
+            let RollingWindow::Time { closed: c, .. } = &mut self.window else {
+                return true;
+            };
+            *c = closed;
+        }
+        false
+    }
+
+    /// Evaluate the expression on each window.
+    ///
+    /// If the expression selects the window, maps it elementwise and then
+    /// aggregates it (e.g. `s(0).abs().sum()`), the result is calculated
+    /// incrementally instead of evaluating the expression on each window.
+    ///
+    /// If `min_periods` is set, the expression should return a scalar for
+    /// each window, the result is none if the window doesn't have enough
+    /// valid values, ints and bools are promoted to option dtype.
+    pub fn apply(mut self, func: Expr) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            axis,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let window = match window {
+            RollingWindow::Fixed(window) => Some(window),
+            RollingWindow::Expanding => None,
+            #[cfg(feature = "time")]
+            RollingWindow::Time { by, period, closed } => {
+                let node = CtxNode {
+                    name: "rolling_apply",
+                    func: Arc::new(move |data, ctx, backend| {
+                        tensure!(!center, "center is not supported for time window");
+                        tensure!(step > 0, "step should be greater than 0");
+                        let bounds = eval_time_bounds(&by, period, closed, ctx)?;
+                        let stride = (step, ffill, par.unwrap_or(false));
+                        match data {
+                            Data::Array(ref arr) if arr.ndim() > 1 => {
+                                lanes_rolling(arr, axis, par, |lane| {
+                                    let out = time_rolling_apply(
+                                        lane,
+                                        &bounds,
+                                        min_periods,
+                                        (step, ffill, false),
+                                        &func,
+                                        Backend::Vec,
+                                    )?;
+                                    Ok(out.into_vec()?.into_owned())
+                                })
+                            }
+                            data => time_rolling_apply(
+                                data,
+                                &bounds,
+                                min_periods,
+                                stride,
+                                &func,
+                                backend,
+                            ),
+                        }
+                    }),
+                };
+                return expr.chain(node);
+            }
+        };
+        let incremental = is_incremental(&func);
         let node = BaseNode {
             name: "rolling_apply",
-            func: Arc::new(move |data: Data, backend| match data {
-                Data::Vec(vec) => vec_rolling(vec, self.window, &func, backend),
-                Data::TrustIter(iter) => match Arc::try_unwrap(iter) {
-                    Ok(iter) => {
-                        vec_rolling(iter.collect_vec()?.into(), self.window, &func, backend)
+            elementwise: false,
+            func: Arc::new(move |data: Data, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(step > 0, "step should be greater than 0");
+                let window = resolve_window(window, &data, center, axis)?;
+                let stride = (step, ffill, par.unwrap_or(false));
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        lanes_rolling(arr, axis, par, |lane| {
+                            let out = strided_rolling_apply(
+                                lane,
+                                window,
+                                min_periods,
+                                center,
+                                (step, ffill, false),
+                                &func,
+                                incremental,
+                                Backend::Vec,
+                            )?;
+                            Ok(out.into_vec()?.into_owned())
+                        })
                     }
-                    Err(_) => {
-                        tbail!("trust iter is shared, cann't collect and rolling shared iter")
+                    data => strided_rolling_apply(
+                        data,
+                        window,
+                        min_periods,
+                        center,
+                        stride,
+                        &func,
+                        incremental,
+                        backend,
+                    ),
+                }
+            }),
+        };
+        expr.chain(node)
+    }
+}
+
+impl Rolling {
+    /// Evaluate the expression on each window of several aligned columns, the
+    /// context of each window contains the window of each column in `exprs`, so
+    /// they can be selected by index or by their output names.
+    ///
+    /// The data of the rolling expression only decides the number of rows, if
+    /// `min_periods` is set a row is valid only if all of the columns are valid.
+    pub fn apply_with<I: IntoIterator<Item = Expr>>(mut self, exprs: I, func: Expr) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let exprs: Vec<Expr> = exprs.into_iter().collect();
+        let names: Vec<Arc<str>> = exprs
+            .iter()
+            .enumerate()
+            .map(|(i, e)| e.name.clone().unwrap_or_else(|| i.to_string().into()))
+            .collect();
+        let node = CtxNode {
+            name: "rolling_apply_with",
+            func: Arc::new(move |data, ctx, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(
+                    !exprs.is_empty(),
+                    "rolling apply_with needs at least one column"
+                );
+                tensure!(step > 0, "step should be greater than 0");
+                let len = data.with_titer(|iter| Ok(iter.len()))?;
+                let bounds = step_bounds(window.bounds(len, center, ctx)?, step);
+                let cols = exprs
+                    .iter()
+                    .map(|e| e.eval(ctx, None)?.with_titer(|iter| iter.collect_vec()))
+                    .collect::<TResult<Vec<_>>>()?;
+                for col in &cols {
+                    tensure!(col.len() == len, lm, col.len(), len);
+                }
+                let out = bounds_rolling_apply(
+                    &cols,
+                    Some(&names),
+                    &bounds,
+                    min_periods,
+                    &func,
+                    par.unwrap_or(false),
+                    backend,
+                )?;
+                broadcast_strided(out, step, ffill, len, backend)
+            }),
+        };
+        expr.chain(node)
+    }
+
+    /// Rolling aggregation of the data and `other`, the rows where either value is
+    /// none are ignored. If the data is a multi dimensional array, `other` can be
+    /// a 1-D array aligned with each lane (e.g. the market returns) or an array of
+    /// the same shape.
+    pub fn pair_agg(mut self, other: Expr, agg: RollingPairAgg) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            axis,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let node = CtxNode {
+            name: agg.name(),
+            func: Arc::new(move |data, ctx, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(step > 0, "step should be greater than 0");
+                let other = other.eval(ctx, None)?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let (shape, axis_idx) = (arr.shape(), axis.unwrap_or(0));
+                        tensure!(axis_idx < shape.len(), "axis {} is out of bounds", axis_idx);
+                        let bounds = window.bounds(shape[axis_idx], center, ctx)?;
+                        let other = match other {
+                            Data::Array(_) => other.into_array()?,
+                            other => {
+                                let other: DynVec = other.collect_f64()?.into();
+                                DynArray::from_vec(other)?
+                            }
+                        };
+                        let out =
+                            arr.rolling_pair_agg(&other, agg, &bounds, min_periods, axis, par)?;
+                        let out: DynArray = stride_array(out, axis, step, ffill).into();
+                        Ok(out.into())
                     }
-                },
-                Data::Array(arr) => array_rolling(arr, self.window, &func, backend),
-                Data::Scalar(_) => tbail!("rolling apply not supported for scalar"),
-                #[cfg(feature = "pl")]
-                Data::Series(series) => series_rolling(series, self.window, &func, backend),
+                    data => {
+                        let x = data.collect_f64()?;
+                        let y = other.collect_f64()?;
+                        tensure!(x.len() == y.len(), lm, x.len(), y.len());
+                        let bounds = window.bounds(x.len(), center, ctx)?;
+                        let out: DynVec =
+                            rolling_pair_agg(&x, &y, agg, &bounds, min_periods).into();
+                        stride_full(out.into_backend(backend)?, step, ffill, backend)
+                    }
+                }
+            }),
+        };
+        expr.chain(node)
+    }
+
+    /// Rolling sample correlation with `other`
+    #[inline]
+    pub fn corr(self, other: Expr) -> Expr {
+        self.pair_agg(other, RollingPairAgg::Corr)
+    }
+
+    /// Rolling sample covariance with `other`
+    #[inline]
+    pub fn cov(self, other: Expr) -> Expr {
+        self.pair_agg(other, RollingPairAgg::Cov)
+    }
+
+    /// Rolling beta to `market`, i.e. `cov(x, market) / var(market)`
+    #[inline]
+    pub fn beta(self, market: Expr) -> Expr {
+        self.pair_agg(market, RollingPairAgg::Beta)
+    }
+
+    /// Rolling order statistic, each window is updated incrementally so a step
+    /// costs O(log n) rather than sorting the window. None values are ignored
+    /// and the output is always float.
+    pub fn order_agg(mut self, agg: RollingOrderAgg) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            axis,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let fixed = match window {
+            RollingWindow::Fixed(window) => Some(window),
+            _ => None,
+        };
+        let mp = Some(min_periods.or(fixed).unwrap_or(1));
+        let node = CtxNode {
+            name: agg.name(),
+            func: Arc::new(move |data, ctx, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(step > 0, "step should be greater than 0");
+                agg.check()?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let (shape, axis_idx) = (arr.shape(), axis.unwrap_or(0));
+                        tensure!(axis_idx < shape.len(), "axis {} is out of bounds", axis_idx);
+                        let bounds = window.bounds(shape[axis_idx], center, ctx)?;
+                        let out = arr.rolling_order_agg(agg, &bounds, mp, axis, par)?;
+                        let out: DynArray = stride_array(out, axis, step, ffill).into();
+                        Ok(out.into())
+                    }
+                    data => {
+                        let values = data.collect_f64()?;
+                        let bounds = window.bounds(values.len(), center, ctx)?;
+                        let out: DynVec = rolling_order_agg(&values, agg, &bounds, mp).into();
+                        stride_full(out.into_backend(backend)?, step, ffill, backend)
+                    }
+                }
             }),
         };
-        self.expr.chain(node)
+        expr.chain(node)
+    }
+
+    /// Rolling weighted aggregation on a fixed or expanding window, the weights
+    /// are aligned with the end of the window. None values are ignored and the
+    /// mean is renormalized by the weights of the valid values.
+    pub fn weighted_agg<W: Into<WindowWeights>>(mut self, weights: W, agg: WeightedAgg) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let weights = weights.into();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            axis,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let (window, time_window) = match window {
+            RollingWindow::Fixed(window) => (Some(window), false),
+            RollingWindow::Expanding => (None, false),
+            #[cfg(feature = "time")]
+            RollingWindow::Time { .. } => (None, true),
+        };
+        let node = CtxNode {
+            name: agg.name(),
+            func: Arc::new(move |data, ctx, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(!time_window, "weighted rolling requires a fixed window");
+                tensure!(step > 0, "step should be greater than 0");
+                let window = resolve_window(window, &data, center, axis)?;
+                tensure!(window > 0, "window should be greater than 0");
+                let weights = weights.eval(window, ctx)?;
+                match data {
+                    Data::Array(ref arr) if arr.ndim() > 1 => {
+                        let out = arr.rolling_weighted_agg(
+                            &weights,
+                            agg,
+                            min_periods,
+                            center,
+                            axis,
+                            par,
+                        )?;
+                        let out: DynArray = stride_array(out, axis, step, ffill).into();
+                        Ok(out.into())
+                    }
+                    data => {
+                        let values = data.collect_f64()?;
+                        let out = rolling_weighted_agg(&values, &weights, agg, min_periods, center);
+                        let out: DynVec = out.into();
+                        stride_full(out.into_backend(backend)?, step, ffill, backend)
+                    }
+                }
+            }),
+        };
+        expr.chain(node)
+    }
+
+    /// Rolling weighted mean, the weights of the missing values are excluded
+    #[inline]
+    pub fn weighted_mean<W: Into<WindowWeights>>(self, weights: W) -> Expr {
+        self.weighted_agg(weights, WeightedAgg::Mean)
+    }
+
+    /// Rolling weighted sum, missing values contribute nothing to the sum
+    #[inline]
+    pub fn weighted_sum<W: Into<WindowWeights>>(self, weights: W) -> Expr {
+        self.weighted_agg(weights, WeightedAgg::Sum)
+    }
+
+    /// Weighted mean with linearly decaying weights `1, 2, ..., window`,
+    /// the latest row has the largest weight
+    #[inline]
+    pub fn decay_linear(self) -> Expr {
+        self.weighted_mean(WeightKernel::Linear)
+    }
+
+    /// Rank of the value of each row in its window (ts_rank), equal values get
+    /// the average rank and the rank is divided by the valid count if `pct` is true
+    #[inline]
+    pub fn rank(self, pct: bool) -> Expr {
+        self.order_agg(RollingOrderAgg::Rank { pct })
+    }
+
+    /// Rolling quantile, `q` should be between 0 and 1
+    #[inline]
+    pub fn quantile(self, q: f64, method: QuantileMethod) -> Expr {
+        self.order_agg(RollingOrderAgg::Quantile { q, method })
+    }
+}
+
+/// get the bounds `[start, end)` of the time window of each row
+#[cfg(feature = "time")]
+fn eval_time_bounds(
+    by: &Expr,
+    period: TimeDelta,
+    closed: ClosedWindow,
+    ctx: &Context,
+) -> TResult<Vec<(usize, usize)>> {
+    by.eval(ctx, None)?
+        .with_titer(|times| times.time_window_bounds(period, closed))
+}
+
+macro_rules! impl_rolling_agg {
+    ($($(#[$meta: meta])* $func: ident => $agg: ident),* $(,)?) => {
+        impl Rolling {
+            $(
+                $(#[$meta])*
+                #[inline]
+                pub fn $func(self) -> Expr {
+                    self.agg(RollingAgg::$agg)
+                }
+            )*
+        }
+    };
+}
+
+impl_rolling_agg!(
+    sum => Sum,
+    mean => Mean,
+    std => Std,
+    var => Var,
+    min => Min,
+    max => Max,
+    skew => Skew,
+    kurt => Kurt,
+    /// Count the valid values in each window
+    count => Count,
+    median => Median,
+);
+
+impl Rolling {
+    /// Rolling aggregation using the incremental kernels, this is much faster
+    /// than `apply` as the expression doesn't need to be evaluated for each window.
+    /// None values are ignored and the output is always float.
+    pub fn agg(mut self, agg: RollingAgg) -> Expr {
+        let invalid_closed = self.resolve_closed();
+        let Rolling {
+            window,
+            min_periods,
+            center,
+            axis,
+            par,
+            step,
+            ffill,
+            expr,
+            ..
+        } = self;
+        let window = match window {
+            RollingWindow::Fixed(window) => Some(window),
+            RollingWindow::Expanding => None,
+            #[cfg(feature = "time")]
+            RollingWindow::Time { by, period, closed } => {
+                let mp = Some(min_periods.unwrap_or(1));
+                let node = CtxNode {
+                    name: agg.name(),
+                    func: Arc::new(move |data, ctx, backend| {
+                        tensure!(!center, "center is not supported for time window");
+                        tensure!(step > 0, "step should be greater than 0");
+                        let bounds = eval_time_bounds(&by, period, closed, ctx)?;
+                        if let Data::Array(arr) = &data {
+                            if arr.ndim() > 1 {
+                                let out = arr.rolling_agg_by(agg, &bounds, mp, axis, par)?;
+                                let out: DynArray = stride_array(out, axis, step, ffill).into();
+                                return Ok(out.into());
+                            }
+                        }
+                        let out: DynVec = data
+                            .with_titer(|iter| iter.rolling_agg_by(agg, &bounds, mp))?
+                            .into();
+                        stride_full(out.into_backend(backend)?, step, ffill, backend)
+                    }),
+                };
+                return expr.chain(node);
+            }
+        };
+        let mp = Some(min_periods.or(window).unwrap_or(1));
+        let node = BaseNode {
+            name: agg.name(),
+            elementwise: false,
+            func: Arc::new(move |data: Data, backend| {
+                tensure!(!invalid_closed, "closed is only supported for time window");
+                tensure!(step > 0, "step should be greater than 0");
+                let window = resolve_window(window, &data, center, axis)?;
+                let out = match data {
+                    Data::Vec(vec) => vec.rolling_agg(agg, window, mp, center)?,
+                    Data::TrustIter(iter) => match Arc::try_unwrap(iter) {
+                        Ok(iter) => iter.collect_vec()?.rolling_agg(agg, window, mp, center)?,
+                        Err(_) => {
+                            tbail!("trust iter is shared, cann't collect and rolling shared iter")
+                        }
+                    },
+                    Data::Array(arr) => {
+                        let out = arr.rolling_agg(agg, window, mp, center, axis, par)?;
+                        if out.ndim() > 1 {
+                            let out: DynArray = stride_array(out, axis, step, ffill).into();
+                            return Ok(out.into());
+                        }
+                        out.into_raw_vec_and_offset().0
+                    }
+                    Data::Scalar(_) => tbail!("rolling is not supported for scalar"),
+                    #[cfg(feature = "pl")]
+                    Data::Series(s) => series_rolling_agg(&s, agg, window, mp, center)?,
+                };
+                let out: DynVec = out.into();
+                stride_full(out.into_backend(backend)?, step, ffill, backend)
+            }),
+        };
+        expr.chain(node)
     }
 }
 
 impl Expr {
     #[inline]
     pub fn rolling(self, window: usize) -> Rolling {
-        Rolling { window, expr: self }
+        Rolling {
+            window: RollingWindow::Fixed(window),
+            #[cfg(feature = "time")]
+            closed: None,
+            min_periods: None,
+            center: false,
+            axis: None,
+            par: None,
+            step: 1,
+            ffill: false,
+            expr: self,
+        }
+    }
+
+    /// Expanding window which grows from the start of the data, the result of
+    /// row `i` is calculated on the rows `[0, i]`. It has the same methods as
+    /// a rolling window and the built-in aggregations are calculated in O(n).
+    #[inline]
+    pub fn expanding(self) -> Rolling {
+        Rolling {
+            window: RollingWindow::Expanding,
+            #[cfg(feature = "time")]
+            closed: None,
+            min_periods: None,
+            center: false,
+            axis: None,
+            par: None,
+            step: 1,
+            ffill: false,
+            expr: self,
+        }
+    }
+
+    /// Rolling window of a time `period`, the rows in the window of each row are
+    /// decided by the time column `by` rather than a row count.
+    ///
+    /// The time column should be sorted in ascending order, by default the window
+    /// of row `i` is `(t_i - period, t_i]`, see [`Rolling::closed`].
+    #[cfg(feature = "time")]
+    #[inline]
+    pub fn rolling_by<P: Into<TimeDelta>>(self, by: Expr, period: P) -> Rolling {
+        Rolling {
+            window: RollingWindow::Time {
+                by,
+                period: period.into(),
+                closed: ClosedWindow::default(),
+            },
+            closed: None,
+            min_periods: None,
+            center: false,
+            axis: None,
+            par: None,
+            step: 1,
+            ffill: false,
+            expr: self,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::expr::methods::eval_f64;
+    use crate::{d1_array, d2_array, d_vec};
+    use tevec::core::testing::assert_vec1d_equal_numeric;
     use tevec::ndarray::arr1;
     #[test]
     fn test_rolling_apply() -> TResult<()> {
@@ -265,6 +1256,551 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_rolling_apply_fast_path() -> TResult<()> {
+        let ctx = Context::new(d_vec![1.5, -2., f64::NAN, f64::NAN, f64::NAN, 4., -0.5]);
+        assert!(is_incremental(&s(0).vabs().sqrt().sum()));
+        // slice makes the expression not incremental
+        assert!(!is_incremental(&s(0).vabs().slice(0, 3).sum()));
+        assert!(!is_incremental(&s(1).sum()));
+        // only the nodes flagged as elementwise are mapped before rolling
+        assert!(is_incremental(&s(0).abs().log(None).round(2).sum()));
+        assert!(!is_incremental(
+            &s(0)
+                .rank(RankMethod::Average, false, false, None, None)
+                .sum()
+        ));
+        let fast = s(0).rolling(3).apply(s(0).vabs().sum());
+        let slow = s(0).rolling(3).apply(s(0).vabs().slice(0, 3).sum());
+        let fast = fast.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        let slow = slow.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        assert_vec1d_equal_numeric(&fast.to_vec(), &slow.to_vec(), None);
+        assert_eq!(fast.as_ref(), &[1.5, 3.5, 3.5, 2., 0., 4., 4.5]);
+        // the small values are kept after the large value leaves the window
+        let ctx = Context::new(d_vec![1e16, 1., 1., 1., 1.]);
+        let res = s(0).rolling(2).apply(s(0).sum());
+        let res = res.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        assert_eq!(res.as_ref(), &[1e16, 1e16 + 1., 2., 2., 2.]);
+        // an inf only affects the windows containing it
+        let ctx = Context::new(d_vec![1., f64::INFINITY, 2., 3., 4.]);
+        let res = s(0).rolling(2).apply(s(0).sum());
+        let res = res.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        assert_eq!(res.as_ref(), &[1., f64::INFINITY, f64::INFINITY, 5., 7.]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_min_periods_center() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new(d_vec![1., 2., nan, 4., 5.]);
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        // the same as pandas
+        let res = eval(s(0).rolling(3).min_periods(2).sum())?;
+        assert_vec1d_equal_numeric(&res, &[nan, 3., 3., 6., 9.], None);
+        let res = eval(s(0).rolling(3).center(true).min_periods(1).mean())?;
+        assert_vec1d_equal_numeric(&res, &[1.5, 1.5, 3., 4.5, 4.5], None);
+        // min_periods defaults to the window length
+        let res = eval(s(0).rolling(2).mean())?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1.5, nan, nan, 4.5], None);
+        let res = eval(s(0).rolling(4).center(true).min_periods(3).max())?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, 4., 5., nan], None);
+        let res = eval(s(0).rolling(3).min_periods(2).apply(s(0).vabs().sum()))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 3., 3., 6., 9.], None);
+        // lazy input
+        let res = eval(s(0).abs().rolling(3).min_periods(2).apply(s(0).sum()))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 3., 3., 6., 9.], None);
+        let res = eval(s(0).abs().rolling(3).center(true).apply(s(0).sum()))?;
+        assert_vec1d_equal_numeric(&res, &[3., 3., 6., 9., 9.], None);
+        // ints are promoted to option
+        let ctx = Context::new(vec![1, 2, 3, 4, 5]);
+        let res = s(0).rolling(3).min_periods(3).apply(s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.opt_i32()?;
+        assert_eq!(res.as_ref(), &[None, None, Some(6), Some(9), Some(12)]);
+        let res = s(0).rolling(3).center(true).apply(s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.i32()?;
+        assert_eq!(res.as_ref(), &[3, 6, 9, 12, 9]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_nd() -> TResult<()> {
+        use tevec::ndarray::arr2;
+        let ctx = Context::new(d2_array![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+        let eval = |e: Expr| -> TResult<Vec<f64>> {
+            let arr = e.eval(&ctx, None)?.into_array()?.f64()?;
+            Ok(arr.view().iter().cloned().collect())
+        };
+        let sum_axis0 = arr2(&[[1., 2., 3.], [5., 7., 9.], [11., 13., 15.]]);
+        let sum_axis1 = arr2(&[[1., 3., 5.], [4., 9., 11.], [7., 15., 17.]]);
+        let res = eval(s(0).rolling(2).min_periods(1).sum())?;
+        assert_eq!(res, sum_axis0.iter().cloned().collect::<Vec<_>>());
+        let res = eval(s(0).rolling(2).axis(1).par(true).min_periods(1).sum())?;
+        assert_eq!(res, sum_axis1.iter().cloned().collect::<Vec<_>>());
+        // evaluate expression on each window of each lane
+        for par in [false, true] {
+            let res = eval(s(0).rolling(2).par(par).apply(s(0).slice(0, 2).sum()))?;
+            assert_eq!(res, sum_axis0.iter().cloned().collect::<Vec<_>>());
+            let res = eval(s(0).rolling(2).axis(1).par(par).apply(s(0).sum()))?;
+            assert_eq!(res, sum_axis1.iter().cloned().collect::<Vec<_>>());
+        }
+        let res = s(0).rolling(2).axis(2).apply(s(0).sum()).eval(&ctx, None);
+        assert!(res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_pair() -> TResult<()> {
+        use tevec::ndarray::arr2;
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1., 2., 3., 4., 5.].into(),
+                d_vec![3., 5., 7., 9., nan].into(),
+                d_vec![1, 2, 3, 4, 5].into(),
+            ],
+            ["x", "y", "z"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s("x").rolling(3).cov(s("y")))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 2., 2., 1.], None);
+        let res = eval(s("x").rolling(3).corr(s("y")))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 1., 1., 1.], None);
+        let res = eval(s("x").rolling(3).min_periods(3).beta(s("y")))?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, 0.5, 0.5, nan], None);
+        // lazy input
+        let res = eval(s("x").abs().rolling(3).corr(s("y")))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 1., 1., 1.], None);
+        let res = eval(s("x").rolling(3).cov(s("y").abs()))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 2., 2., 1.], None);
+        let res = s("x")
+            .abs()
+            .rolling(2)
+            .apply_with([s("z").abs()], s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.i32()?;
+        assert_eq!(res.as_ref(), &[1, 3, 5, 7, 9]);
+        // the context of each window contains several columns
+        let res = s("x")
+            .rolling(2)
+            .apply_with([s("y"), s("z")], s("z").sum())
+            .eval(&ctx, None)?
+            .into_vec()?
+            .i32()?;
+        assert_eq!(res.as_ref(), &[1, 3, 5, 7, 9]);
+        let res = eval(
+            s("x")
+                .rolling(2)
+                .min_periods(2)
+                .apply_with([s("y"), s("x")], s(1).sum()),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[nan, 3., 5., 7., nan], None);
+        // the co-moments don't lose precision for large values and an inf only
+        // affects the windows containing it
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1e9 + 1., 1e9 + 2., 1e9 + 4., 1e9 + 3., 1e9 + 5.].into(),
+                d_vec![1., f64::INFINITY, 2., 4., 3.].into(),
+            ],
+            ["x", "y"],
+        );
+        let res = s("x").rolling(3).cov(s("x")).eval(&ctx, None)?;
+        let res = res.into_vec()?.f64()?.to_vec();
+        assert_vec1d_equal_numeric(&res, &[nan, 0.5, 7. / 3., 1., 1.], Some(1e-6));
+        let res = s("x").rolling(2).corr(s("y")).eval(&ctx, None)?;
+        let res = res.into_vec()?.f64()?.to_vec();
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, -1., -1.], None);
+        // beta of each asset to the market
+        let ctx = Context::new_from_data_column(
+            vec![
+                d2_array![[1., -1.], [2., -2.], [3., -3.], [4., -4.]].into(),
+                d_vec![3., 5., 7., 9.].into(),
+            ],
+            ["panel", "market"],
+        );
+        for par in [false, true] {
+            let res = s("panel")
+                .rolling(3)
+                .par(par)
+                .beta(s("market"))
+                .eval(&ctx, None)?
+                .into_array()?
+                .f64()?;
+            let expect = arr2(&[[nan, nan], [0.5, -0.5], [0.5, -0.5], [0.5, -0.5]]);
+            assert_vec1d_equal_numeric(
+                &res.view().iter().cloned().collect::<Vec<_>>(),
+                &expect.iter().cloned().collect::<Vec<_>>(),
+                None,
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_expanding() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new(d_vec![1., 3., nan, 5., 2.]);
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s(0).expanding().sum())?;
+        assert_vec1d_equal_numeric(&res, &[1., 4., 4., 9., 11.], None);
+        let res = eval(s(0).expanding().mean())?;
+        assert_vec1d_equal_numeric(&res, &[1., 2., 2., 3., 2.75], None);
+        let res = eval(s(0).expanding().min())?;
+        assert_vec1d_equal_numeric(&res, &[1., 1., 1., 1., 1.], None);
+        let res = eval(s(0).expanding().max())?;
+        assert_vec1d_equal_numeric(&res, &[1., 3., 3., 5., 5.], None);
+        let res = eval(s(0).expanding().std())?;
+        let std = [nan, 2f64.sqrt(), 2f64.sqrt(), 2., (35. / 12f64).sqrt()];
+        assert_vec1d_equal_numeric(&res, &std, None);
+        let res = eval(s(0).expanding().min_periods(3).mean())?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, 3., 2.75], None);
+        // evaluate expression on each window
+        let res = eval(s(0).expanding().apply(s(0).vabs().sum()))?;
+        assert_vec1d_equal_numeric(&res, &[1., 4., 4., 9., 11.], None);
+        let res = eval(s(0).expanding().apply(s(0).slice(0, 2).sum()))?;
+        assert_vec1d_equal_numeric(&res, &[1., 4., 4., 4., 4.], None);
+        assert!(eval(s(0).expanding().center(true).sum()).is_err());
+        // expanding along an axis
+        let ctx = Context::new(d2_array![[1., 2.], [3., 4.], [5., 6.]]);
+        let res = s(0)
+            .expanding()
+            .axis(1)
+            .mean()
+            .eval(&ctx, None)?
+            .into_array()?
+            .f64()?;
+        let res: Vec<f64> = res.view().iter().cloned().collect();
+        assert_eq!(res, vec![1., 1.5, 3., 3.5, 5., 5.5]);
+        let res = s(0)
+            .expanding()
+            .apply(s(0).sum())
+            .eval(&ctx, None)?
+            .into_array()?
+            .f64()?;
+        let res: Vec<f64> = res.view().iter().cloned().collect();
+        assert_eq!(res, vec![1., 2., 4., 6., 9., 12.]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_step() -> TResult<()> {
+        let ctx = Context::new(d_vec![1., 2., 3., 4., 5., 6., 7.]);
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s(0).rolling(3).step(2).sum())?;
+        assert_vec1d_equal_numeric(&res, &[f64::NAN, 6., 12., 18.], None);
+        let res = eval(s(0).rolling(3).step(2).ffill(true).min_periods(1).sum())?;
+        assert_eq!(res, vec![1., 1., 6., 6., 12., 12., 18.]);
+        // only the windows of the evaluated rows are calculated
+        let res = eval(s(0).rolling(3).step(3).apply(s(0).slice(0, 3).sum()))?;
+        assert_eq!(res, vec![1., 9., 18.]);
+        let res = eval(
+            s(0).rolling(3)
+                .step(3)
+                .ffill(true)
+                .apply(s(0).slice(0, 3).sum()),
+        )?;
+        assert_eq!(res, vec![1., 1., 1., 9., 9., 9., 18.]);
+        let res = eval(s(0).rolling(3).step(3).apply(s(0).vabs().sum()))?;
+        assert_eq!(res, vec![1., 9., 18.]);
+        assert!(eval(s(0).rolling(3).step(0).sum()).is_err());
+        // strided rolling along an axis
+        let ctx = Context::new(d2_array![[1., 2.], [3., 4.], [5., 6.], [7., 8.]]);
+        for e in [
+            s(0).rolling(2).step(2).min_periods(1).sum(),
+            s(0).rolling(2).step(2).apply(s(0).slice(0, 2).sum()),
+        ] {
+            let res = e.eval(&ctx, None)?.into_array()?.f64()?;
+            assert_eq!(res.view().shape(), &[2, 2]);
+            let res: Vec<f64> = res.view().iter().cloned().collect();
+            assert_eq!(res, vec![1., 2., 8., 10.]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_order() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new(d_vec![3., 1., nan, 2., 2., 5., 4.]);
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s(0).rolling(3).rank(false))?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, nan, nan, 3., 2.], None);
+        let res = eval(s(0).rolling(3).min_periods(1).rank(false))?;
+        assert_vec1d_equal_numeric(&res, &[1., 1., nan, 2., 1.5, 3., 2.], None);
+        let res = eval(s(0).rolling(3).min_periods(1).rank(true))?;
+        let expect = [1., 0.5, nan, 1., 0.75, 1., 2. / 3.];
+        assert_vec1d_equal_numeric(&res, &expect, None);
+        let res = eval(s(0).rolling(3).min_periods(2).rank(false))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., nan, 2., 1.5, 3., 2.], None);
+        // lazy input
+        let res = eval(s(0).abs().rolling(3).min_periods(1).rank(false))?;
+        assert_vec1d_equal_numeric(&res, &[1., 1., nan, 2., 1.5, 3., 2.], None);
+        let res = eval(
+            s(0).abs()
+                .rolling(3)
+                .min_periods(1)
+                .quantile(0.5, QuantileMethod::Linear),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[3., 2., 2., 1.5, 2., 2., 4.], None);
+        // the same as pandas
+        let rolling = |window| s(0).rolling(window).min_periods(1);
+        let res = eval(rolling(3).quantile(0.5, QuantileMethod::Linear))?;
+        assert_vec1d_equal_numeric(&res, &[3., 2., 2., 1.5, 2., 2., 4.], None);
+        let res = eval(rolling(4).quantile(0.25, QuantileMethod::Linear))?;
+        assert_vec1d_equal_numeric(&res, &[3., 1.5, 1.5, 1.5, 1.5, 2., 2.], None);
+        let res = eval(rolling(4).quantile(0.25, QuantileMethod::Lower))?;
+        assert_vec1d_equal_numeric(&res, &[3., 1., 1., 1., 1., 2., 2.], None);
+        let res = eval(rolling(4).quantile(0.25, QuantileMethod::Higher))?;
+        assert_vec1d_equal_numeric(&res, &[3., 3., 3., 2., 2., 2., 2.], None);
+        let res = eval(s(0).expanding().quantile(1., QuantileMethod::MidPoint))?;
+        assert_vec1d_equal_numeric(&res, &[3., 3., 3., 3., 3., 5., 5.], None);
+        // the same as the median kernel, including the default min_periods
+        let res = eval(s(0).rolling(4).quantile(0.5, QuantileMethod::Linear))?;
+        let median = eval(s(0).rolling(4).median())?;
+        assert_vec1d_equal_numeric(&res, &median, None);
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, nan, nan, nan, 3.], None);
+        let res = eval(rolling(4).quantile(0.5, QuantileMethod::Linear))?;
+        let median = eval(rolling(4).median())?;
+        assert_vec1d_equal_numeric(&res, &median, None);
+        assert!(eval(s(0).rolling(3).quantile(1.5, QuantileMethod::Linear)).is_err());
+        // rank along an axis
+        let ctx = Context::new(d2_array![[1., 4.], [3., 2.], [2., 3.]]);
+        for par in [false, true] {
+            let res = s(0)
+                .rolling(2)
+                .min_periods(1)
+                .par(par)
+                .rank(false)
+                .eval(&ctx, None)?
+                .into_array()?
+                .f64()?;
+            let res: Vec<f64> = res.view().iter().cloned().collect();
+            assert_eq!(res, vec![1., 1., 2., 1., 1., 2.]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_weighted() -> TResult<()> {
+        let nan = f64::NAN;
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![1., 2., nan, 4., 5.].into(),
+                d_vec![0.2, 0.3, 0.5].into(),
+                d_vec![1., 2., 3., 4., 5.].into(),
+            ],
+            ["x", "w", "y"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s("x").rolling(3).decay_linear())?;
+        assert_vec1d_equal_numeric(&res, &[1., 1.6, 5. / 3., 3.5, 4.6], None);
+        let res = eval(s("x").rolling(3).weighted_sum(WeightKernel::Linear))?;
+        assert_vec1d_equal_numeric(&res, &[3., 8., 5., 14., 23.], None);
+        let res = eval(s("x").rolling(3).weighted_sum(s("w")))?;
+        assert_vec1d_equal_numeric(&res, &[0.5, 1.3, 0.8, 2.4, 3.7], None);
+        // lazy input and weights
+        let res = eval(s("x").abs().rolling(3).decay_linear())?;
+        assert_vec1d_equal_numeric(&res, &[1., 1.6, 5. / 3., 3.5, 4.6], None);
+        let res = eval(s("x").abs().rolling(3).weighted_sum(s("w").abs()))?;
+        assert_vec1d_equal_numeric(&res, &[0.5, 1.3, 0.8, 2.4, 3.7], None);
+        let res = eval(s("x").rolling(3).min_periods(2).weighted_sum(s("w")))?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1.3, 0.8, 2.4, 3.7], None);
+        let res = eval(
+            s("x")
+                .rolling(2)
+                .weighted_mean(WeightKernel::Exp(EwmDecay::Alpha(0.5))),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[1., 5. / 3., 2., 4., 14. / 3.], None);
+        let res = eval(s("y").rolling(3).weighted_mean(WeightKernel::Triangular))?;
+        assert_vec1d_equal_numeric(&res, &[1., 4. / 3., 2., 3., 4.], None);
+        let res = eval(s("y").rolling(3).center(true).decay_linear())?;
+        assert_vec1d_equal_numeric(&res, &[1.6, 14. / 6., 20. / 6., 26. / 6., 14. / 3.], None);
+        assert!(eval(s("x").rolling(2).weighted_mean(s("w"))).is_err());
+        // weighted mean along an axis
+        let ctx = Context::new(d2_array![[1., 4.], [3., 2.], [2., 3.]]);
+        let res = s(0)
+            .rolling(2)
+            .decay_linear()
+            .eval(&ctx, None)?
+            .into_array()?
+            .f64()?;
+        let res: Vec<f64> = res.view().iter().cloned().collect();
+        assert_vec1d_equal_numeric(&res, &[1., 4., 7. / 3., 8. / 3., 7. / 3., 8. / 3.], None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_par_apply() -> TResult<()> {
+        let ctx = Context::new(DynVec::from(
+            (0..1000).map(|v| v as f64).collect::<Vec<_>>(),
+        ));
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let func = s(0).slice(0, 3).sum();
+        let expect = eval(s(0).rolling(5).apply(func.clone()))?;
+        let res = eval(s(0).rolling(5).par(true).apply(func.clone()))?;
+        assert_eq!(res, expect);
+        let expect = eval(s(0).rolling(5).step(3).apply(func.clone()))?;
+        let res = eval(s(0).rolling(5).step(3).par(true).apply(func))?;
+        assert_eq!(res, expect);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_rolling_by() -> TResult<()> {
+        let day = 86_400_000;
+        let dt = |d: i64| DateTime::<unit::Millisecond>::new(d * day);
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![dt(0), dt(1), dt(2), dt(5), dt(6), dt(8)].into(),
+                d_vec![1, 2, 3, 4, 5, 6].into(),
+            ],
+            ["t", "v"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let nan = f64::NAN;
+        let res = eval(s("v").rolling_by(s("t"), "3d").sum())?;
+        assert_eq!(res, vec![1., 3., 6., 4., 9., 11.]);
+        let res = eval(
+            s("v")
+                .rolling_by(s("t"), "3d")
+                .closed(ClosedWindow::Both)
+                .sum(),
+        )?;
+        assert_eq!(res, vec![1., 3., 6., 7., 9., 15.]);
+        let res = eval(
+            s("v")
+                .rolling_by(s("t"), "3d")
+                .closed(ClosedWindow::Left)
+                .sum(),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 3., 3., 4., 9.], None);
+        let period = TimeDelta::parse("3d")?;
+        let res = eval(
+            s("v")
+                .rolling_by(s("t"), period)
+                .closed(ClosedWindow::None)
+                .max(),
+        )?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1., 2., nan, 4., 5.], None);
+        let res = eval(s("v").rolling_by(s("t"), "3d").min_periods(2).mean())?;
+        assert_vec1d_equal_numeric(&res, &[nan, 1.5, 2., nan, 4.5, 5.5], None);
+        // evaluate expression on each window
+        let res = s("v").rolling_by(s("t"), "3d").apply(s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.i32()?;
+        assert_eq!(res.as_ref(), &[1, 3, 6, 4, 9, 11]);
+        let res = s("v")
+            .rolling_by(s("t"), "3d")
+            .min_periods(2)
+            .apply(s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.opt_i32()?;
+        assert_eq!(
+            res.as_ref(),
+            &[None, Some(3), Some(6), None, Some(9), Some(11)]
+        );
+        // lazy input
+        let res = eval(s("v").abs().rolling_by(s("t"), "3d").sum())?;
+        assert_eq!(res, vec![1., 3., 6., 4., 9., 11.]);
+        let res = s("v").abs().rolling_by(s("t"), "3d").apply(s(0).sum());
+        let res = res.eval(&ctx, None)?.into_vec()?.i32()?;
+        assert_eq!(res.as_ref(), &[1, 3, 6, 4, 9, 11]);
+        // closed is only supported for time window, no node is added for it
+        let rolling = s("v").rolling(3).closed(ClosedWindow::Both);
+        assert!(rolling.clone().sum().eval(&ctx, None).is_err());
+        assert!(rolling.clone().median().eval(&ctx, None).is_err());
+        assert!(rolling
+            .clone()
+            .quantile(0.5, QuantileMethod::Linear)
+            .eval(&ctx, None)
+            .is_err());
+        assert!(rolling.clone().corr(s("v")).eval(&ctx, None).is_err());
+        assert!(rolling.clone().decay_linear().eval(&ctx, None).is_err());
+        assert!(rolling
+            .clone()
+            .apply_with([s("v")], s(0).sum())
+            .eval(&ctx, None)
+            .is_err());
+        let res = rolling.apply(s(0).sum());
+        assert_eq!(res.nodes.len(), 2);
+        assert!(res.eval(&ctx, None).is_err());
+        let res = s("v")
+            .expanding()
+            .closed(ClosedWindow::Left)
+            .apply(s(0).slice(0, 2).sum());
+        assert!(res.eval(&ctx, None).is_err());
+        // the moments don't lose precision for large values and an inf only
+        // affects the windows containing it
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![dt(0), dt(1), dt(2), dt(3), dt(4), dt(5)].into(),
+                d_vec![1e9 + 1., 1e9 + 2., 1e9 + 3., 1e9 + 4., 1e9 + 5., 1e9 + 6.].into(),
+                d_vec![1., f64::INFINITY, 2., 3., 4., 6.].into(),
+            ],
+            ["t", "v", "w"],
+        );
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let res = eval(s("v").rolling_by(s("t"), "3d").var())?;
+        assert_vec1d_equal_numeric(&res, &[nan, 0.5, 1., 1., 1., 1.], None);
+        let res = eval(s("v").rolling_by(s("t"), "3d").skew())?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, 0., 0., 0., 0.], None);
+        let res = eval(s("v").rolling_by(s("t"), "4d").kurt())?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, -1.2, -1.2, -1.2], None);
+        let res = eval(s("w").rolling_by(s("t"), "2d").sum())?;
+        assert_eq!(res, vec![1., f64::INFINITY, f64::INFINITY, 5., 7., 10.]);
+        let res = eval(s("w").rolling_by(s("t"), "2d").var())?;
+        assert_vec1d_equal_numeric(&res, &[nan, nan, nan, 0.5, 0.5, 2.], None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_agg() -> TResult<()> {
+        let ctx = Context::new(d_vec![1., 5., f64::NAN, 3., 2., 8.]);
+        let eval = |e: Expr| eval_f64(e, &ctx);
+        let nan = f64::NAN;
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).sum())?,
+            &[1., 6., 6., 8., 5., 13.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).mean())?,
+            &[1., 3., 3., 4., 2.5, 13. / 3.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).min())?,
+            &[1., 1., 1., 3., 2., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).max())?,
+            &[1., 5., 5., 5., 3., 8.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).count())?,
+            &[1., 2., 2., 2., 2., 3.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &eval(s(0).rolling(3).min_periods(1).median())?,
+            &[1., 3., 3., 4., 2.5, 3.],
+            None,
+        );
+        let var = eval(s(0).rolling(3).min_periods(1).var())?;
+        assert_vec1d_equal_numeric(&var, &[nan, 8., 8., 2., 0.5, 31. / 3.], None);
+        let std = eval(s(0).rolling(3).min_periods(1).std())?;
+        assert_vec1d_equal_numeric(
+            &std,
+            &var.iter().map(|v| v.sqrt()).collect::<Vec<_>>(),
+            None,
+        );
+        // rolling in a lazy iterator and array
+        let res = eval(s(0).abs().rolling(2).sum())?;
+        assert_vec1d_equal_numeric(&res, &[nan, 6., nan, nan, 5., 10.], None);
+        let ctx = Context::new(d1_array![1, 2, 3, 4]);
+        let res = s(0).rolling(2).mean().eval(&ctx, None)?.into_vec()?.f64()?;
+        assert_vec1d_equal_numeric(&res.to_vec(), &[nan, 1.5, 2.5, 3.5], None);
+        Ok(())
+    }
+
     #[test]
     #[cfg(feature = "pl")]
     fn test_rolling_apply_pl() -> TResult<()> {
@@ -280,6 +1816,52 @@ mod tests {
         let ctx = Context::new(v);
         let res = expr.eval(&ctx, Some(Backend::Polars))?.into_series()?;
         assert!(res.equals(&Series::new("sum", &[1, 3, 6, 9, 12, 15, 18, 21, 24])));
+        // built-in rolling aggregation
+        let v = Series::new("a", &[1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let ctx = Context::new(v);
+        let res = s(0).rolling(3).min_periods(1).sum();
+        let res = res.eval(&ctx, None)?.into_series()?;
+        assert!(res.equals(&Series::new("", &[1., 3., 6., 9., 12., 15., 18., 21., 24.])));
+        // windows of a float series with none values
+        let v = Series::new("a", &[Some(1.), None, Some(3.), Some(4.)]);
+        let ctx = Context::new(v);
+        let res = s(0).rolling(2).apply(s(0).sum()).eval(&ctx, None)?;
+        let res = res.into_vec()?.f64()?;
+        assert_eq!(res.as_ref(), &[1., 1., 3., 7.]);
+        // unsupported dtype is an error rather than a panic
+        let ctx = Context::new(Series::new("a", &["a", "b", "c"]));
+        assert!(expr.eval(&ctx, None).is_err());
+        // the expression is not incremental so it is evaluated on the slices
+        let expr = s(0).rolling(3).apply(s(0).slice(0, 2).sum()).alias("sum");
+        assert!(!is_incremental(&s(0).slice(0, 2).sum()));
+        let v = Series::new("a", &[1., 2., 3., 4., 5., 6., 7., 8., 9.]);
+        let ctx = Context::new(v);
+        let res = expr.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        assert_eq!(res.as_ref(), &[1., 3., 3., 5., 7., 9., 11., 13., 15.]);
+        let res = expr.eval(&ctx, Some(Backend::Polars))?.into_series()?;
+        let expect = Series::new("sum", &[1., 3., 3., 5., 7., 9., 11., 13., 15.]);
+        assert!(res.equals(&expect));
+        let res = s(0)
+            .rolling(3)
+            .min_periods(3)
+            .apply(s(0).slice(0, 2).sum())
+            .eval(&ctx, Some(Backend::Vec))?
+            .into_vec()?
+            .f64()?;
+        let expect = [f64::NAN, f64::NAN, 3., 5., 7., 9., 11., 13., 15.];
+        assert_vec1d_equal_numeric(&res.to_vec(), &expect, None);
+        let res = s(0)
+            .rolling(3)
+            .center(true)
+            .apply(s(0).slice(0, 2).sum())
+            .eval(&ctx, Some(Backend::Polars))?
+            .into_series()?;
+        let expect = Series::new("", &[3., 3., 5., 7., 9., 11., 13., 15., 17.]);
+        assert!(res.equals(&expect));
+        // the windows of an unsupported dtype fail in the slow path as well
+        let ctx = Context::new(Series::new("a", &["a", "b", "c"]));
+        assert!(expr.eval(&ctx, Some(Backend::Vec)).is_err());
+        assert!(expr.eval(&ctx, Some(Backend::Polars)).is_err());
         Ok(())
     }
 }
@@ -0,0 +1,266 @@
+use crate::prelude::*;
+
+/// slice the data along the first axis, borrowed data and array views
+/// are sliced without copying
+#[allow(clippy::missing_transmute_annotations)]
+fn slice_data(data: Data<'_>, offset: i64, len: usize) -> TResult<Data<'_>> {
+    match data {
+        Data::TrustIter(iter) => match Arc::try_unwrap(iter) {
+            Ok(iter) => {
+                let (start, end) = slice_bound(offset, len, iter.len());
+                Ok(iter.slice(start, end)?.into())
+            }
+            Err(_) => tbail!("trust iter is shared, cann't slice shared iter"),
+        },
+        Data::Vec(vec) => {
+            let (start, end) = slice_bound(offset, len, vec.len());
+            match Arc::try_unwrap(vec) {
+                Ok(vec) => Ok(vec.into_slice(start, end)?.into()),
+                Err(vec) => {
+                    // the data is still shared
+                    // this should only happen when the data is stored in a context
+                    // so it is safe to reference data
+                    let vec: DynVec<'_> = unsafe { std::mem::transmute(vec.slice(start, end)?) };
+                    Ok(vec.into())
+                }
+            }
+        }
+        Data::Array(ref arr) => {
+            let total = arr.shape().into_iter().next().unwrap_or(0);
+            let (start, end) = slice_bound(offset, len, total);
+            Ok(data.into_array()?.slice_axis(0, start, end)?.into())
+        }
+        Data::Scalar(_) => tbail!("slice is not supported for scalar"),
+        #[cfg(feature = "pl")]
+        Data::Series(s) => Ok(s.slice(offset, len).into()),
+    }
+}
+
+/// get the length of the data along the axis
+pub(super) fn data_len(data: &Data<'_>, axis: usize) -> TResult<usize> {
+    match data {
+        Data::TrustIter(iter) => Ok(iter.len()),
+        Data::Vec(vec) => Ok(vec.len()),
+        Data::Array(arr) => {
+            let shape = arr.shape();
+            tensure!(axis < shape.len(), "axis {} out of bounds", axis);
+            Ok(shape[axis])
+        }
+        Data::Scalar(_) => Ok(1),
+        #[cfg(feature = "pl")]
+        Data::Series(s) => Ok(s.len()),
+    }
+}
+
+fn eval_mask(mask: Data<'_>) -> TResult<Vec<bool>> {
+    match mask.try_into_iter() {
+        Ok(iter) => iter.into_mask(),
+        Err(mask) => mask.try_titer()?.into_mask(),
+    }
+}
+
+fn eval_index(idx: Data<'_>, len: usize) -> TResult<Vec<usize>> {
+    match idx.try_into_iter() {
+        Ok(iter) => iter.into_index(len),
+        Err(idx) => idx.try_titer()?.into_index(len),
+    }
+}
+
+/// take the elements at the given indices, array with ndim > 1 is taken along the axis
+pub(super) fn take_data(
+    data: Data<'_>,
+    idx: Vec<usize>,
+    axis: usize,
+    backend: Backend,
+) -> TResult<Data<'_>> {
+    match data {
+        Data::Array(ref arr) if arr.ndim() > 1 => Ok(arr.select_axis(axis, &idx)?.into()),
+        #[cfg(feature = "pl")]
+        Data::Series(s) => {
+            use tevec::polars::prelude::{IdxCa, IdxSize};
+            let idx = IdxCa::from_vec("", idx.into_iter().map(|i| i as IdxSize).collect());
+            Ok(s.take(&idx).map_err(|e| terr!("{}", e))?.into())
+        }
+        _ => match data.try_into_iter() {
+            Ok(iter) => Ok(iter.take(idx)?.into()),
+            Err(data) => data.try_titer()?.take(idx)?.collect(backend),
+        },
+    }
+}
+
+/// keep the elements where the mask is true, array with ndim > 1 is filtered along the axis
+fn filter_data(
+    data: Data<'_>,
+    mask: Vec<bool>,
+    axis: usize,
+    backend: Backend,
+) -> TResult<Data<'_>> {
+    let len = data_len(&data, axis)?;
+    tensure!(mask.len() == len, lm, mask.len(), len);
+    match data {
+        Data::Array(ref arr) if arr.ndim() > 1 => {
+            let idx: Vec<usize> = mask
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, m)| m.then_some(i))
+                .collect();
+            Ok(arr.select_axis(axis, &idx)?.into())
+        }
+        #[cfg(feature = "pl")]
+        Data::Series(s) => {
+            use tevec::polars::prelude::{BooleanChunked, NewChunkedArray};
+            let mask = BooleanChunked::from_slice("", &mask);
+            Ok(s.filter(&mask).map_err(|e| terr!("{}", e))?.into())
+        }
+        _ => match data.try_into_iter() {
+            Ok(iter) => Ok(iter.filter(mask)?.into()),
+            Err(data) => data.try_titer()?.filter(mask)?.collect(backend),
+        },
+    }
+}
+
+impl Expr {
+    /// Get a slice of the data, negative offset counts from the end.
+    /// For array with ndim > 1, the data is sliced along the first axis.
+    pub fn slice(self, offset: i64, len: usize) -> Self {
+        let node = BaseNode {
+            name: "slice",
+            elementwise: false,
+            func: Arc::new(move |data, _backend| slice_data(data, offset, len)),
+        };
+        self.chain(node)
+    }
+
+    #[inline]
+    pub fn head(self, n: usize) -> Self {
+        self.slice(0, n)
+    }
+
+    #[inline]
+    pub fn tail(self, n: usize) -> Self {
+        self.slice(-(n as i64), n)
+    }
+
+    /// Keep the elements where the mask is true, none values in mask are
+    /// treated as false. For array with ndim > 1, the data is filtered along
+    /// the axis (default 0).
+    pub fn filter(self, mask: Expr, axis: Option<usize>) -> Self {
+        let node = CtxNode {
+            name: "filter",
+            func: Arc::new(move |data, ctx, backend| {
+                let mask = eval_mask(mask.eval(ctx, None)?)?;
+                filter_data(data, mask, axis.unwrap_or(0), backend)
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Take the elements at the given indices, negative index counts from the end.
+    /// For array with ndim > 1, the data is taken along the axis (default 0).
+    pub fn gather(self, idx: Expr, axis: Option<usize>) -> Self {
+        let node = CtxNode {
+            name: "gather",
+            func: Arc::new(move |data, ctx, backend| {
+                let axis = axis.unwrap_or(0);
+                let idx = eval_index(idx.eval(ctx, None)?, data_len(&data, axis)?)?;
+                take_data(data, idx, axis, backend)
+            }),
+        };
+        self.chain(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{d2_array, d_vec};
+    use std::borrow::Cow;
+    use tevec::ndarray::prelude::*;
+
+    #[test]
+    fn test_slice() -> TResult<()> {
+        let ctx = Context::new(vec![1, 2, 3, 4, 5]);
+        let res = s(0).head(2).eval(&ctx, None)?.into_vec()?.i32()?;
+        // the data in context should be sliced without copying
+        assert!(matches!(res, Cow::Borrowed(&[1, 2])));
+        let res = s(0).tail(2).eval(&ctx, None)?;
+        assert_eq!(res.try_titer()?.i32()?.collect::<Vec<_>>(), vec![4, 5]);
+        let res = s(0).slice(-4, 2).eval(&ctx, None)?;
+        assert_eq!(res.try_titer()?.i32()?.collect::<Vec<_>>(), vec![2, 3]);
+        // lazy iterator
+        let res = s(0).abs().slice(1, 10).eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[2, 3, 4, 5]);
+        // slice array along the first axis
+        let ctx = Context::new(d2_array![[1, 2], [3, 4], [5, 6]]);
+        let res = s(0).tail(2).eval(&ctx, None)?.into_array()?.i32()?;
+        let expect: Array2<i32> = arr2(&[[3, 4], [5, 6]]);
+        assert_eq!(res.view().into_dimensionality().unwrap(), expect.view());
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_gather() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![3, 1, 4, 1, 5].into(),
+                d_vec![true, false, true, false, true].into(),
+                d_vec![0usize, 4].into(),
+            ],
+            ["a", "mask", "idx"],
+        );
+        let res = s("a")
+            .filter(s("mask"), None)
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[3, 4, 5]);
+        let res = s("a")
+            .gather(s("idx"), None)
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[3, 5]);
+        let res = s("a")
+            .gather(lit(-2i64), None)
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[1]);
+        assert!(s("a").gather(lit(5i64), None).eval(&ctx, None).is_err());
+        assert!(s("a").gather(lit(-6i64), None).eval(&ctx, None).is_err());
+        // top 2 values
+        let top = s("a").gather(s("a").arg_sort(true, true).head(2), None);
+        let res = top.eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[5, 4]);
+        // filter and gather array along an axis
+        let ctx = Context::new_from_data_column(
+            vec![
+                d2_array![[1, 2, 3], [4, 5, 6]].into(),
+                d_vec![true, false, true].into(),
+            ],
+            ["a", "mask"],
+        );
+        let res = s("a").filter(s("mask"), Some(1)).eval(&ctx, None)?;
+        let expect: Array2<i32> = arr2(&[[1, 3], [4, 6]]);
+        let res = res.into_array()?.i32()?;
+        assert_eq!(res.view().into_dimensionality().unwrap(), expect.view());
+        let res = s("a").gather(lit(-1i64), Some(0)).eval(&ctx, None)?;
+        let expect: Array2<i32> = arr2(&[[4, 5, 6]]);
+        let res = res.into_array()?.i32()?;
+        assert_eq!(res.view().into_dimensionality().unwrap(), expect.view());
+        assert!(s("a").filter(s("mask"), Some(0)).eval(&ctx, None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "pl")]
+    fn test_filter_gather_pl() -> TResult<()> {
+        use tevec::polars::prelude::*;
+        let ctx = Context::new_from_data_column(
+            vec![
+                Series::new("a", &[3, 1, 4, 1, 5]).into(),
+                Series::new("mask", &[true, false, true, false, true]).into(),
+            ],
+            ["a", "mask"],
+        );
+        let res = s("a").filter(s("mask"), None).eval(&ctx, None)?;
+        assert!(res.into_series()?.equals(&Series::new("a", &[3, 4, 5])));
+        let res = s("a").gather(lit(-1i64), None).eval(&ctx, None)?;
+        assert!(res.into_series()?.equals(&Series::new("a", &[5])));
+        Ok(())
+    }
+}
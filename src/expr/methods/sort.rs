@@ -0,0 +1,244 @@
+use super::select::take_data;
+use crate::prelude::*;
+
+/// get the index set of top k values of each lane if the data is an array with ndim > 1
+fn lane_top_k<'b>(
+    data: &Data<'_>,
+    k: usize,
+    descending: bool,
+    axis: Option<usize>,
+    par: Option<bool>,
+) -> Option<TResult<Data<'b>>> {
+    match data {
+        Data::Array(arr) if arr.ndim() > 1 => Some(
+            arr.arg_top_k(k, descending, axis, par)
+                .map(|arr| arr.into()),
+        ),
+        _ => None,
+    }
+}
+
+impl Expr {
+    pub fn sort(self, descending: bool, nulls_last: bool) -> Self {
+        let node = BaseNode {
+            name: "sort",
+            elementwise: false,
+            func: Arc::new(move |data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.sort(descending, nulls_last)?.into()),
+                Err(data) => {
+                    if let Ok(iter) = data.try_titer() {
+                        return iter.sort(descending, nulls_last)?.collect(backend);
+                    }
+                    tbail!("sort is not supported for array with ndim > 1")
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Get the indices that would sort the data, the sort is stable
+    pub fn arg_sort(self, descending: bool, nulls_last: bool) -> Self {
+        let node = BaseNode {
+            name: "arg_sort",
+            elementwise: false,
+            func: Arc::new(move |data, backend| {
+                let idx = match data.try_into_iter() {
+                    Ok(iter) => iter.arg_sort(descending, nulls_last)?,
+                    Err(data) => data.try_titer()?.arg_sort(descending, nulls_last)?,
+                };
+                let idx: DynVec = idx.into();
+                idx.into_backend(backend)
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Sort the data using the values of another expression as keys,
+    /// none keys are always placed at the end
+    pub fn sort_by(self, by: Expr, descending: bool) -> Self {
+        let node = CtxNode {
+            name: "sort_by",
+            func: Arc::new(move |data, ctx, backend| {
+                let by = by.eval(ctx, None)?;
+                let idx = match by.try_into_iter() {
+                    Ok(iter) => iter.arg_sort(descending, true)?,
+                    Err(by) => by.try_titer()?.arg_sort(descending, true)?,
+                };
+                match data.try_into_iter() {
+                    Ok(iter) => {
+                        tensure!(iter.len() == idx.len(), "length of sort keys mismatch");
+                        Ok(iter.take(idx)?.into())
+                    }
+                    Err(data) => {
+                        let iter = data.try_titer()?;
+                        tensure!(iter.len() == idx.len(), "length of sort keys mismatch");
+                        iter.take(idx)?.collect(backend)
+                    }
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn select_k(
+        self,
+        name: &'static str,
+        k: usize,
+        by: Option<Expr>,
+        descending: bool,
+        return_idx: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        let node = CtxNode {
+            name,
+            func: Arc::new(move |data, ctx, backend| {
+                let by = by.as_ref().map(|by| by.eval(ctx, None)).transpose()?;
+                let lane_out = match &by {
+                    Some(by) => lane_top_k(by, k, descending, axis, par),
+                    None => lane_top_k(&data, k, descending, axis, par),
+                };
+                if let Some(out) = lane_out {
+                    return out;
+                }
+                let idx = match by {
+                    Some(by) => match by.try_into_iter() {
+                        Ok(iter) => iter.arg_top_k(k, descending)?,
+                        Err(by) => by.try_titer()?.arg_top_k(k, descending)?,
+                    },
+                    None if return_idx => {
+                        let idx: DynVec = match data.try_into_iter() {
+                            Ok(iter) => iter.arg_top_k(k, descending)?,
+                            Err(data) => data.try_titer()?.arg_top_k(k, descending)?,
+                        }
+                        .into();
+                        return idx.into_backend(backend);
+                    }
+                    None => {
+                        return match data.try_into_iter() {
+                            Ok(iter) => Ok(iter.top_k(k, descending)?.into()),
+                            Err(data) => data.try_titer()?.top_k(k, descending)?.collect(backend),
+                        }
+                    }
+                };
+                if return_idx {
+                    let idx: DynVec = idx.into();
+                    idx.into_backend(backend)
+                } else {
+                    take_data(data, idx, 0, backend)
+                }
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Select the `k` largest values in O(n), the result is sorted in descending order
+    /// and none values are never selected.
+    ///
+    /// If `by` is given, the values are selected by the keys of `by`. If `return_idx`
+    /// is true, the indices are returned instead of the values. For array with ndim > 1,
+    /// the index set of each lane along `axis` is always returned.
+    pub fn top_k(
+        self,
+        k: usize,
+        by: Option<Expr>,
+        return_idx: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        self.select_k("top_k", k, by, true, return_idx, axis, par)
+    }
+
+    /// Select the `k` smallest values in O(n), see [`Expr::top_k`]
+    pub fn bottom_k(
+        self,
+        k: usize,
+        by: Option<Expr>,
+        return_idx: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> Self {
+        self.select_k("bottom_k", k, by, false, return_idx, axis, par)
+    }
+
+    /// Reverse the data, for array the data is reversed along the first
+    /// axis without copying
+    pub fn reverse(self) -> Self {
+        let node = BaseNode {
+            name: "reverse",
+            elementwise: false,
+            func: Arc::new(|data, backend| match data {
+                Data::Array(_) => Ok(data.into_array()?.invert_axis(0)?.into()),
+                #[cfg(feature = "pl")]
+                Data::Series(s) => Ok(s.reverse().into()),
+                _ => match data.try_into_iter() {
+                    Ok(iter) => Ok(iter.reverse()?.into()),
+                    Err(data) => data.try_titer()?.reverse()?.collect(backend),
+                },
+            }),
+        };
+        self.chain(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{d2_array, d_vec};
+
+    #[test]
+    fn test_sort() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![3.0, f64::NAN, 1.0, 2.0].into(),
+                d_vec![2, 4, 3, 1].into(),
+            ],
+            ["a", "b"],
+        );
+        let res = s("a").sort(false, true).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?;
+        assert_eq!(&res[..3], &[1.0, 2.0, 3.0]);
+        assert!(res[3].is_nan());
+        let res = s("a").sort(true, false).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?;
+        assert!(res[0].is_nan());
+        assert_eq!(&res[1..], &[3.0, 2.0, 1.0]);
+        let res = s("a").arg_sort(false, true).eval(&ctx, None)?;
+        assert_eq!(res.into_vec()?.usize()?.as_ref(), &[2, 3, 0, 1]);
+        let res = s("b")
+            .sort_by(s("a"), true)
+            .eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[2, 1, 3, 4]);
+        let res = s("b").reverse().eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.i32()?.as_ref(), &[1, 3, 4, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_k() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![3.0, f64::NAN, 1.0, 5.0, 2.0].into(),
+                d_vec![1, 2, 3, 4, 5].into(),
+            ],
+            ["a", "b"],
+        );
+        let res = s("a").top_k(2, None, false, None, None);
+        let res = res.eval(&ctx, Some(Backend::Vec))?.into_vec()?.f64()?;
+        assert_eq!(res.as_ref(), &[5.0, 3.0]);
+        let res = s("a")
+            .bottom_k(10, None, true, None, None)
+            .eval(&ctx, None)?;
+        assert_eq!(res.into_vec()?.usize()?.as_ref(), &[2, 4, 0, 3]);
+        let res = s("b").top_k(2, Some(s("a")), false, None, None);
+        let res = res.eval(&ctx, Some(Backend::Vec))?.into_vec()?.i32()?;
+        assert_eq!(res.as_ref(), &[4, 1]);
+        // index set of each row
+        let ctx = Context::new(d2_array![[1, 4, 2], [6, 5, 3]]);
+        let res = s(0).top_k(2, None, true, Some(1), None).eval(&ctx, None)?;
+        let res = res.into_array()?.vec_usize()?;
+        assert_eq!(res.view().as_slice().unwrap(), &[vec![1, 2], vec![0, 1]]);
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use crate::prelude::*;
+
+impl Expr {
+    /// Get the unique values, if `maintain_order` is false the values are sorted.
+    /// NaN values are treated as equal.
+    pub fn unique(self, maintain_order: bool) -> Self {
+        let node = BaseNode {
+            name: "unique",
+            elementwise: false,
+            func: Arc::new(move |data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.unique(maintain_order)?.into()),
+                Err(data) => data.try_titer()?.unique(maintain_order)?.collect(backend),
+            }),
+        };
+        self.chain(node)
+    }
+
+    pub fn n_unique(self) -> Self {
+        let node = BaseNode {
+            name: "n_unique",
+            elementwise: false,
+            func: Arc::new(|data, _backend| {
+                let n = match data.try_into_iter() {
+                    Ok(iter) => iter.n_unique()?,
+                    Err(data) => data.try_titer()?.n_unique()?,
+                };
+                Ok(n.into())
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Whether the value appears more than once
+    pub fn is_duplicated(self) -> Self {
+        let node = BaseNode {
+            name: "is_duplicated",
+            elementwise: false,
+            func: Arc::new(|data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.is_duplicated()?.into()),
+                Err(data) => data.try_titer()?.is_duplicated()?.collect(backend),
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Whether the value appears only once
+    pub fn is_unique(self) -> Self {
+        let node = BaseNode {
+            name: "is_unique",
+            elementwise: false,
+            func: Arc::new(|data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.is_unique()?.into()),
+                Err(data) => data.try_titer()?.is_unique()?.collect(backend),
+            }),
+        };
+        self.chain(node)
+    }
+
+    /// Whether the value is the first occurrence
+    pub fn is_first(self) -> Self {
+        let node = BaseNode {
+            name: "is_first",
+            elementwise: false,
+            func: Arc::new(|data, backend| match data.try_into_iter() {
+                Ok(iter) => Ok(iter.is_first()?.into()),
+                Err(data) => data.try_titer()?.is_first()?.collect(backend),
+            }),
+        };
+        self.chain(node)
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Count the occurrences of each unique value of the expression, the
+    /// expression is evaluated and hashed only once.
+    ///
+    /// Return a new context with the unique values, named after the expression,
+    /// and their counts in the `count` column. If `sort` is true they are sorted
+    /// by count in descending order, otherwise they are in order of first appearance.
+    pub fn value_counts(&self, expr: Expr, sort: bool) -> TResult<Context<'a>> {
+        let backend = self.backend.unwrap_or_default();
+        let name = expr.name.clone().unwrap_or_else(|| "values".into());
+        let (values, counts) = match expr.eval(self, Some(backend))?.try_into_iter() {
+            Ok(iter) => {
+                let (values, counts) = iter.value_counts(sort)?;
+                (values.collect(backend)?, counts)
+            }
+            Err(data) => {
+                let (values, counts) = data.try_titer()?.value_counts(sort)?;
+                let values = values
+                    .collect(backend)?
+                    .into_owned(Some(backend))
+                    .map_err(|_| terr!("the unique values can not be owned"))?;
+                (values, counts)
+            }
+        };
+        let counts: DynVec = counts.into();
+        let data = vec![values, counts.into_backend(backend)?];
+        Ok(Context::new_from_data_column(data, [name, "count".into()]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d_vec;
+
+    #[test]
+    fn test_unique() -> TResult<()> {
+        let ctx = Context::new_from_data_column(
+            vec![
+                d_vec![2.0, f64::NAN, 1.0, 2.0, f64::NAN, -0.0, 0.0].into(),
+                d_vec!["b".to_owned(), "a".to_owned(), "b".to_owned()].into(),
+            ],
+            ["a", "b"],
+        );
+        let res = s("a").unique(true).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?;
+        assert_eq!(res.len(), 4);
+        assert_eq!((res[0], res[2], res[3]), (2.0, 1.0, 0.0));
+        assert!(res[1].is_nan());
+        let res = s("a").unique(false).eval(&ctx, Some(Backend::Vec))?;
+        let res = res.into_vec()?.f64()?;
+        assert_eq!(&res[..3], &[0.0, 1.0, 2.0]);
+        assert!(res[3].is_nan());
+        let res = s("a").n_unique().eval(&ctx, None)?.into_scalar()?.usize()?;
+        assert_eq!(res, 4);
+        let out = ctx.value_counts(s("b"), true)?;
+        assert!(out.get("b").is_ok() && out.get("count").is_ok());
+        let mut cols = out.data.into_iter();
+        let values = cols.next().unwrap().into_vec()?.string()?.to_vec();
+        let counts = cols.next().unwrap().into_vec()?.usize()?.to_vec();
+        assert_eq!(values, vec!["b".to_owned(), "a".to_owned()]);
+        assert_eq!(counts, vec![2, 1]);
+        // the expression is evaluated lazily
+        let out = ctx.value_counts(s("a").abs(), false)?;
+        let counts = out.data.into_iter().nth(1).unwrap().into_vec()?.usize()?;
+        assert_eq!(counts.as_ref(), &[2, 2, 1, 2]);
+        let res = s("a").is_duplicated().eval(&ctx, Some(Backend::Vec))?;
+        let expect = [true, true, false, true, true, true, true];
+        assert_eq!(res.into_vec()?.bool()?.as_ref(), &expect);
+        let res = s("b").is_unique().eval(&ctx, Some(Backend::Vec))?;
+        assert_eq!(res.into_vec()?.bool()?.as_ref(), &[false, true, false]);
+        let res = s("a").is_first().eval(&ctx, Some(Backend::Vec))?;
+        let expect = [true, true, true, false, false, true, false];
+        assert_eq!(res.into_vec()?.bool()?.as_ref(), &expect);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_unique_datetime() -> TResult<()> {
+        let dt = |v| DateTime::<unit::Millisecond>::new(v);
+        let ctx = Context::new(d_vec![
+            dt(1),
+            dt(2),
+            dt(1),
+            DateTime::nat(),
+            DateTime::nat()
+        ]);
+        let res = s(0).n_unique().eval(&ctx, None)?.into_scalar()?.usize()?;
+        assert_eq!(res, 3);
+        let res = s(0).is_duplicated().eval(&ctx, Some(Backend::Vec))?;
+        let expect = [true, false, true, true, true];
+        assert_eq!(res.into_vec()?.bool()?.as_ref(), &expect);
+        Ok(())
+    }
+}
@@ -77,4 +77,67 @@ mod tests {
         assert_eq!(res.view().into_dimensionality().unwrap(), expect.view());
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "map")]
+    fn test_rank() -> TResult<()> {
+        use crate::prelude::RankMethod;
+        use tevec::core::testing::assert_vec1d_equal_numeric;
+        use tevec::ndarray::prelude::*;
+        let ctx = Context::new(d_vec![3.0, f64::NAN, 1.0, 3.0, 2.0]);
+        let rank = |method, descending, pct| -> TResult<Vec<f64>> {
+            let res = s(0)
+                .rank(method, descending, pct, None, None)
+                .eval(&ctx, None)?
+                .into_vec()?
+                .f64()?;
+            Ok(res.into_owned())
+        };
+        let nan = f64::NAN;
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Average, false, false)?,
+            &vec![3.5, nan, 1., 3.5, 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Min, false, false)?,
+            &vec![3., nan, 1., 3., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Max, false, false)?,
+            &vec![4., nan, 1., 4., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Dense, false, false)?,
+            &vec![3., nan, 1., 3., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Ordinal, false, false)?,
+            &vec![3., nan, 1., 4., 2.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Ordinal, true, false)?,
+            &vec![1., nan, 4., 2., 3.],
+            None,
+        );
+        assert_vec1d_equal_numeric(
+            &rank(RankMethod::Dense, false, true)?,
+            &vec![1., nan, 1. / 3., 1., 2. / 3.],
+            None,
+        );
+        // rank cross-sectionally along the last axis
+        let ctx = Context::new(d2_array![[1, 3, 2], [6, 5, 4]]);
+        let res = s(0)
+            .rank(RankMethod::Average, false, false, Some(1), None)
+            .eval(&ctx, None)?
+            .into_array()?
+            .f64()?;
+        let expect: Array2<f64> = arr2(&[[1., 3., 2.], [3., 2., 1.]]);
+        assert_eq!(res.view().into_dimensionality().unwrap(), expect.view());
+        Ok(())
+    }
 }
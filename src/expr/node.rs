@@ -59,7 +59,10 @@ impl<T: Into<Symbol>> From<T> for SelectNode {
 // #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BaseNode {
     pub name: &'static str,
-    pub func: Arc<dyn for<'a> Fn(Data<'a>, Backend) -> TResult<Data<'a>>>,
+    /// whether the node maps each value independently, such node keeps the
+    /// length of the data and commutes with slicing
+    pub elementwise: bool,
+    pub func: Arc<dyn for<'a> Fn(Data<'a>, Backend) -> TResult<Data<'a>> + Send + Sync>,
 }
 
 #[derive(Clone)]
@@ -67,5 +70,5 @@ pub struct BaseNode {
 // the node also require context to execute other expressions
 pub struct CtxNode {
     pub name: &'static str,
-    pub func: Arc<dyn for<'a> Fn(Data<'a>, &Context, Backend) -> TResult<Data<'a>>>,
+    pub func: Arc<dyn for<'a> Fn(Data<'a>, &Context, Backend) -> TResult<Data<'a>> + Send + Sync>,
 }
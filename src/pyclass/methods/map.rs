@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use pyo3::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
 
 #[pymethods]
 impl PyExpr {
@@ -26,4 +26,23 @@ impl PyExpr {
         let value = value.map(|v| v.0.clone());
         Ok(self.clone().0.vshift(n, value, axis, par).into())
     }
+
+    #[pyo3(signature=(method="average", descending=false, pct=false, axis=None, par=None))]
+    pub fn rank(
+        &self,
+        method: &str,
+        descending: bool,
+        pct: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> PyResult<Self> {
+        let method: RankMethod = method
+            .parse()
+            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+        Ok(self
+            .clone()
+            .0
+            .rank(method, descending, pct, axis, par)
+            .into())
+    }
 }
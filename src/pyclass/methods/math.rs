@@ -0,0 +1,86 @@
+use crate::prelude::*;
+use pyo3::prelude::*;
+
+macro_rules! impl_py_map {
+    ($($func: ident, $vfunc: ident);* $(;)?) => {
+        #[pymethods]
+        impl PyExpr {
+            $(
+                #[pyo3(signature=(nan=true))]
+                pub fn $func(&self, nan: bool) -> Self {
+                    if nan {
+                        self.clone().0.$vfunc().into()
+                    } else {
+                        self.clone().0.$func().into()
+                    }
+                }
+            )*
+        }
+    };
+}
+
+impl_py_map!(
+    sqrt, vsqrt;
+    cbrt, vcbrt;
+    exp, vexp;
+    log1p, vlog1p;
+    sin, vsin;
+    cos, vcos;
+    tan, vtan;
+    asin, vasin;
+    acos, vacos;
+    atan, vatan;
+    sinh, vsinh;
+    cosh, vcosh;
+    tanh, vtanh;
+    sign, vsign;
+    floor, vfloor;
+    ceil, vceil;
+);
+
+#[pymethods]
+impl PyExpr {
+    #[pyo3(signature=(base=None, nan=true))]
+    pub fn log(&self, base: Option<f64>, nan: bool) -> Self {
+        if nan {
+            self.clone().0.vlog(base).into()
+        } else {
+            self.clone().0.log(base).into()
+        }
+    }
+
+    #[pyo3(signature=(decimals=0, nan=true))]
+    pub fn round(&self, decimals: u32, nan: bool) -> Self {
+        if nan {
+            self.clone().0.vround(decimals).into()
+        } else {
+            self.clone().0.round(decimals).into()
+        }
+    }
+
+    #[pyo3(signature=(exponent, nan=true))]
+    pub fn pow(&self, exponent: &Bound<'_, PyAny>, nan: bool) -> PyResult<Self> {
+        let exponent: PyExpr = exponent.try_into()?;
+        if nan {
+            Ok(self.clone().0.vpow(exponent.0).into())
+        } else {
+            Ok(self.clone().0.pow(exponent.0).into())
+        }
+    }
+
+    #[pyo3(signature=(lower=None, upper=None, nan=true))]
+    pub fn clip(
+        &self,
+        lower: Option<&Bound<'_, PyAny>>,
+        upper: Option<&Bound<'_, PyAny>>,
+        nan: bool,
+    ) -> PyResult<Self> {
+        let lower = lower.map(PyExpr::try_from).transpose()?.map(|e| e.0);
+        let upper = upper.map(PyExpr::try_from).transpose()?.map(|e| e.0);
+        if nan {
+            Ok(self.clone().0.vclip(lower, upper).into())
+        } else {
+            Ok(self.clone().0.clip(lower, upper).into())
+        }
+    }
+}
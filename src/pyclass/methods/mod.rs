@@ -1,2 +1,10 @@
 #[cfg(feature = "map")]
 mod map;
+#[cfg(feature = "map")]
+mod math;
+#[cfg(feature = "map")]
+mod select;
+#[cfg(feature = "map")]
+mod sort;
+#[cfg(feature = "map")]
+mod unique;
@@ -0,0 +1,31 @@
+use crate::prelude::*;
+use pyo3::prelude::*;
+
+#[pymethods]
+impl PyExpr {
+    pub fn slice(&self, offset: i64, len: usize) -> Self {
+        self.clone().0.slice(offset, len).into()
+    }
+
+    #[pyo3(signature=(n=10))]
+    pub fn head(&self, n: usize) -> Self {
+        self.clone().0.head(n).into()
+    }
+
+    #[pyo3(signature=(n=10))]
+    pub fn tail(&self, n: usize) -> Self {
+        self.clone().0.tail(n).into()
+    }
+
+    #[pyo3(signature=(mask, axis=None))]
+    pub fn filter(&self, mask: &Bound<'_, PyAny>, axis: Option<usize>) -> PyResult<Self> {
+        let mask: PyExpr = mask.try_into()?;
+        Ok(self.clone().0.filter(mask.0, axis).into())
+    }
+
+    #[pyo3(signature=(idx, axis=None))]
+    pub fn gather(&self, idx: &Bound<'_, PyAny>, axis: Option<usize>) -> PyResult<Self> {
+        let idx: PyExpr = idx.try_into()?;
+        Ok(self.clone().0.gather(idx.0, axis).into())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::prelude::*;
+use pyo3::prelude::*;
+
+#[pymethods]
+impl PyExpr {
+    #[pyo3(signature=(descending=false, nulls_last=true))]
+    pub fn sort(&self, descending: bool, nulls_last: bool) -> Self {
+        self.clone().0.sort(descending, nulls_last).into()
+    }
+
+    #[pyo3(signature=(descending=false, nulls_last=true))]
+    pub fn arg_sort(&self, descending: bool, nulls_last: bool) -> Self {
+        self.clone().0.arg_sort(descending, nulls_last).into()
+    }
+
+    #[pyo3(signature=(by, descending=false))]
+    pub fn sort_by(&self, by: &Bound<'_, PyAny>, descending: bool) -> PyResult<Self> {
+        let by: PyExpr = by.try_into()?;
+        Ok(self.clone().0.sort_by(by.0, descending).into())
+    }
+
+    pub fn reverse(&self) -> Self {
+        self.clone().0.reverse().into()
+    }
+
+    #[pyo3(signature=(k, by=None, return_idx=false, axis=None, par=None))]
+    pub fn top_k(
+        &self,
+        k: usize,
+        by: Option<&Bound<'_, PyAny>>,
+        return_idx: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> PyResult<Self> {
+        let by = by.map(PyExpr::try_from).transpose()?.map(|e| e.0);
+        Ok(self.clone().0.top_k(k, by, return_idx, axis, par).into())
+    }
+
+    #[pyo3(signature=(k, by=None, return_idx=false, axis=None, par=None))]
+    pub fn bottom_k(
+        &self,
+        k: usize,
+        by: Option<&Bound<'_, PyAny>>,
+        return_idx: bool,
+        axis: Option<usize>,
+        par: Option<bool>,
+    ) -> PyResult<Self> {
+        let by = by.map(PyExpr::try_from).transpose()?.map(|e| e.0);
+        Ok(self.clone().0.bottom_k(k, by, return_idx, axis, par).into())
+    }
+}
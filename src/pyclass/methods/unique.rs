@@ -0,0 +1,49 @@
+use crate::prelude::*;
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+#[pymethods]
+impl PyExpr {
+    #[pyo3(signature=(maintain_order=false))]
+    pub fn unique(&self, maintain_order: bool) -> Self {
+        self.clone().0.unique(maintain_order).into()
+    }
+
+    pub fn n_unique(&self) -> Self {
+        self.clone().0.n_unique().into()
+    }
+
+    /// evaluate the expression once and return the unique values and their counts
+    #[pyo3(signature=(ctx=None, sort=false, backend=None))]
+    pub fn value_counts<'py>(
+        &'py self,
+        ctx: Option<Bound<'py, PyAny>>,
+        sort: bool,
+        backend: Option<&'py Bound<'py, PyAny>>,
+        py: Python<'py>,
+    ) -> PyResult<(PyObject, PyObject)> {
+        let ctx_obj = ctx.clone();
+        let mut ctx: Context<'py> = ctx.map(|c| c.extract().unwrap()).unwrap_or_default();
+        if let Some(backend) = backend {
+            ctx.backend = Some(backend.extract()?);
+        }
+        let out = ctx
+            .value_counts(self.0.clone(), sort)
+            .map_err(|e| PyValueError::new_err(format!("{}", e)))?;
+        let mut cols = out.data.into_iter();
+        let values = cols.next().unwrap().try_into_py(py, ctx_obj.clone())?;
+        let counts = cols.next().unwrap().try_into_py(py, ctx_obj)?;
+        Ok((values, counts))
+    }
+
+    pub fn is_duplicated(&self) -> Self {
+        self.clone().0.is_duplicated().into()
+    }
+
+    pub fn is_unique(&self) -> Self {
+        self.clone().0.is_unique().into()
+    }
+
+    pub fn is_first(&self) -> Self {
+        self.clone().0.is_first().into()
+    }
+}